@@ -0,0 +1,698 @@
+//! Offline analysis of audio files
+//!
+//! Files are decoded as fast as possible and fed through the live metering
+//! callback, so peak, RMS and clip counts match what the meter shows. True
+//! peak, loudness, noise floor and DC offset are measured alongside.
+
+use crate::audio;
+use crate::channels::{self, ChannelSpec};
+use crate::decode::AudioFile;
+use crate::error::{AppError, AppResult};
+use crate::smoothing::Ballistics;
+use crate::state::SharedState;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+/// Measurements for one channel of a file
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelReport {
+    pub channel: usize,
+    /// Highest sample peak in dBFS
+    pub peak_db: f32,
+    /// Highest inter-sample peak in dBTP, from 4x oversampling
+    pub true_peak_db: f32,
+    pub rms_db: f32,
+    /// Integrated loudness (ITU-R BS.1770) of the channel on its own
+    pub loudness_lufs: f32,
+    /// Level the quietest 10% of the file stays below, in dBFS
+    pub noise_floor_db: f32,
+    /// Mean sample value as a fraction of full scale
+    pub dc_offset: f32,
+    /// Number of clip events
+    pub clips: u64,
+}
+
+/// Measurements for a whole file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub sample_rate: u32,
+    pub duration_s: f64,
+    /// Integrated loudness of all analyzed channels together
+    pub loudness_lufs: f32,
+    pub channels: Vec<ChannelReport>,
+}
+
+/// Quality problems found in a file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Flags {
+    /// A channel reached full scale
+    pub clipped: bool,
+    /// Every channel stays below the silence level
+    pub silent: bool,
+    /// A channel has a DC offset above the limit
+    pub dc_offset: bool,
+    /// The file is quieter than the loudness limit
+    pub too_quiet: bool,
+}
+
+impl Flags {
+    /// Names of the flags that are set
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.clipped, "clipped"),
+            (self.silent, "silent"),
+            (self.dc_offset, "dc-offset"),
+            (self.too_quiet, "too-quiet"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect()
+    }
+
+    /// Whether the file passes with no flags set
+    pub fn passed(&self) -> bool {
+        *self == Flags::default()
+    }
+}
+
+impl FileReport {
+    /// Highest sample peak of any channel
+    pub fn peak_db(&self) -> f32 {
+        self.channels
+            .iter()
+            .map(|ch| ch.peak_db)
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// Highest true peak of any channel
+    pub fn true_peak_db(&self) -> f32 {
+        self.channels
+            .iter()
+            .map(|ch| ch.true_peak_db)
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// Clip events across all channels
+    pub fn clips(&self) -> u64 {
+        self.channels.iter().map(|ch| ch.clips).sum()
+    }
+
+    /// Check the file against the QC limits
+    pub fn flags(&self) -> Flags {
+        use crate::constants::analyze::{DC_OFFSET_LIMIT, TOO_QUIET_LUFS};
+        let silent = self.true_peak_db() < crate::constants::events::SILENCE_DB;
+        Flags {
+            clipped: self.clips() > 0,
+            silent,
+            dc_offset: self
+                .channels
+                .iter()
+                .any(|ch| ch.dc_offset.abs() > DC_OFFSET_LIMIT),
+            // Silence is reported on its own
+            too_quiet: !silent && self.loudness_lufs < TOO_QUIET_LUFS,
+        }
+    }
+}
+
+impl fmt::Display for FileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {:.3} s at {} Hz, loudness {:.1} LUFS",
+            self.path.display(),
+            self.duration_s,
+            self.sample_rate,
+            self.loudness_lufs
+        )?;
+        for ch in &self.channels {
+            writeln!(
+                f,
+                "  Channel {}: peak {:.1} dB, true peak {:.1} dBTP, RMS {:.1} dB, loudness {:.1} LUFS, noise floor {:.1} dB, DC offset {:+.5}, {} clips",
+                ch.channel,
+                ch.peak_db,
+                ch.true_peak_db,
+                ch.rms_db,
+                ch.loudness_lufs,
+                ch.noise_floor_db,
+                ch.dc_offset,
+                ch.clips
+            )?;
+        }
+        let flags = self.flags().names();
+        if !flags.is_empty() {
+            writeln!(f, "  Flags: {}", flags.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Channels of a file with `total_channels` that `channels` selects, all by default
+///
+/// Ranges and `all` expand as they do for a device's inputs.
+fn file_channels(channels: Option<&[ChannelSpec]>, total_channels: usize) -> AppResult<Vec<usize>> {
+    match channels {
+        Some(specs) => Ok(channels::resolve(specs, |_| Ok(total_channels))?.channels),
+        None => Ok((0..total_channels).collect()),
+    }
+}
+
+/// Decode and measure `path`, using all channels unless `channels` selects some
+pub fn analyze_file(path: &Path, channels: Option<&[ChannelSpec]>) -> AppResult<FileReport> {
+    let mut file = AudioFile::open(path)?;
+    let total_channels = file.channels();
+    let sample_rate = file.sample_rate();
+    let selected = file_channels(channels, total_channels)?;
+    if let Some(&ch) = selected.iter().find(|&&ch| ch >= total_channels) {
+        return Err(AppError::AudioFile(format!(
+            "Channel {} not in {} ({} channels)",
+            ch,
+            path.display(),
+            total_channels
+        )));
+    }
+    tracing::debug!(path = %path.display(), sample_rate, total_channels, "Analyzing file");
+
+    // Meter exactly like a live stream, with a threshold nothing reaches
+    let shared_state = SharedState::new(selected.len(), f32::INFINITY);
+    let mut callback = audio::create_audio_callback(
+        shared_state.clone(),
+        Ballistics::default(),
+        &selected,
+        total_channels,
+        sample_rate,
+    );
+    let mut meters: Vec<_> = selected
+        .iter()
+        .map(|_| ChannelMeter::new(sample_rate))
+        .collect();
+
+    let mut buffer = Vec::new();
+    let mut frames = 0u64;
+    while file.read_block(crate::constants::analyze::BLOCK_FRAMES, &mut buffer)? {
+        callback(&buffer, None);
+        for (meter, &ch) in meters.iter_mut().zip(&selected) {
+            for &sample in buffer.iter().skip(ch).step_by(total_channels) {
+                meter.add(sample);
+            }
+        }
+        frames += (buffer.len() / total_channels) as u64;
+    }
+
+    // Loudness of all channels together sums their weighted power
+    let mut combined_hops = vec![
+        0.0;
+        meters
+            .iter()
+            .map(|m| m.weighted_hops.len())
+            .min()
+            .unwrap_or(0)
+    ];
+    for meter in &meters {
+        for (sum, power) in combined_hops.iter_mut().zip(&meter.weighted_hops) {
+            *sum += power;
+        }
+    }
+
+    let channels = selected
+        .iter()
+        .zip(shared_state.channels())
+        .zip(meters)
+        .map(|((&channel, levels), meter)| {
+            let sample_count = levels.sample_count.load(Ordering::Relaxed);
+            let mean_square = levels.sum_of_squares.load() / sample_count.max(1) as f64;
+            let measured = meter.finish();
+            ChannelReport {
+                channel,
+                // Measured here rather than by the live meter, which stops at -60 dB
+                peak_db: measured.peak_db,
+                true_peak_db: measured.true_peak_db,
+                rms_db: power_to_db(mean_square),
+                loudness_lufs: measured.loudness_lufs,
+                noise_floor_db: measured.noise_floor_db,
+                dc_offset: measured.dc_offset,
+                clips: levels.clip_count.load(Ordering::Relaxed),
+            }
+        })
+        .collect();
+
+    Ok(FileReport {
+        path: path.to_path_buf(),
+        sample_rate,
+        duration_s: frames as f64 / sample_rate as f64,
+        loudness_lufs: gated_loudness(&combined_hops),
+        channels,
+    })
+}
+
+/// Analyze many files in parallel, handing each result to `report` in order
+///
+/// A result is passed on as soon as every file before it in `paths` is done,
+/// so long batches show progress instead of waiting for the last file.
+pub fn analyze_files(
+    paths: &[PathBuf],
+    channels: Option<&[ChannelSpec]>,
+    mut report: impl FnMut(&Path, AppResult<FileReport>),
+) {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            paths
+                .par_iter()
+                .enumerate()
+                .for_each_with(tx, |tx, (i, path)| {
+                    let _ = tx.send((i, analyze_file(path, channels)));
+                });
+        });
+
+        let mut finished = BTreeMap::new();
+        let mut next = 0;
+        for (i, result) in rx {
+            finished.insert(i, result);
+            while let Some(result) = finished.remove(&next) {
+                report(&paths[next], result);
+                next += 1;
+            }
+        }
+    });
+}
+
+/// Files found by `collect_files`
+pub struct FoundFiles {
+    pub files: Vec<PathBuf>,
+    /// Directory entries that could not be read, with the reason
+    pub unreadable: Vec<(PathBuf, AppError)>,
+}
+
+/// Expand directories in `paths` into the audio files they contain
+///
+/// Directories are only searched with `recursive`; files named directly are
+/// always kept, whatever their extension. Entries that cannot be read while
+/// searching are returned separately so the rest of the batch still runs.
+pub fn collect_files(paths: &[PathBuf], recursive: bool) -> AppResult<FoundFiles> {
+    let mut files = Vec::new();
+    let mut unreadable = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+        if !recursive {
+            return Err(AppError::AudioFile(format!(
+                "{} is a directory (use --recursive to analyze its files)",
+                path.display()
+            )));
+        }
+
+        let mut found = Vec::new();
+        for entry in walkdir::WalkDir::new(path).follow_links(true) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    let entry_path = e.path().unwrap_or(path).to_path_buf();
+                    tracing::warn!(path = %entry_path.display(), error = %e, "Skipping unreadable entry");
+                    unreadable.push((entry_path, AppError::Io(e.into())));
+                    continue;
+                }
+            };
+            if entry.file_type().is_file() && is_audio_file(entry.path()) {
+                found.push(entry.into_path());
+            }
+        }
+        found.sort();
+        files.extend(found);
+    }
+    Ok(FoundFiles { files, unreadable })
+}
+
+/// Whether `path` has the extension of a format that can be decoded
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            crate::constants::analyze::EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Measurements the live meter does not make
+struct Measured {
+    peak_db: f32,
+    true_peak_db: f32,
+    loudness_lufs: f32,
+    noise_floor_db: f32,
+    dc_offset: f32,
+}
+
+/// Per-channel measurement state, fed one sample at a time
+struct ChannelMeter {
+    peak: f32,
+    true_peak: TruePeak,
+    k_filter: [Biquad; 2],
+    sum: f64,
+    count: u64,
+    hop_len: usize,
+    hop_frames: usize,
+    hop_weighted: f64,
+    hop_plain: f64,
+    /// K-weighted mean square of each 100 ms hop
+    weighted_hops: Vec<f64>,
+    /// Unweighted level of each 100 ms hop in dB
+    hop_levels: Vec<f32>,
+}
+
+impl ChannelMeter {
+    fn new(sample_rate: u32) -> Self {
+        Self {
+            peak: 0.0,
+            true_peak: TruePeak::new(),
+            k_filter: k_weighting(sample_rate as f64),
+            sum: 0.0,
+            count: 0,
+            hop_len: (sample_rate as usize / 10).max(1),
+            hop_frames: 0,
+            hop_weighted: 0.0,
+            hop_plain: 0.0,
+            weighted_hops: Vec::new(),
+            hop_levels: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, sample: f32) {
+        self.peak = self.peak.max(sample.abs());
+        self.true_peak.add(sample);
+        self.sum += sample as f64;
+        self.count += 1;
+
+        let plain = sample as f64;
+        let weighted = self.k_filter.iter_mut().fold(plain, |x, f| f.process(x));
+        self.hop_weighted += weighted * weighted;
+        self.hop_plain += plain * plain;
+        self.hop_frames += 1;
+        if self.hop_frames == self.hop_len {
+            let n = self.hop_len as f64;
+            self.weighted_hops.push(self.hop_weighted / n);
+            self.hop_levels.push(power_to_db(self.hop_plain / n));
+            self.hop_frames = 0;
+            self.hop_weighted = 0.0;
+            self.hop_plain = 0.0;
+        }
+    }
+
+    fn finish(mut self) -> Measured {
+        Measured {
+            peak_db: amplitude_to_db(self.peak as f64),
+            true_peak_db: amplitude_to_db(self.true_peak.finish() as f64),
+            loudness_lufs: gated_loudness(&self.weighted_hops),
+            noise_floor_db: if self.hop_levels.is_empty() {
+                crate::constants::analyze::FLOOR_DB
+            } else {
+                crate::stats::percentile(
+                    &self.hop_levels,
+                    crate::constants::analyze::NOISE_FLOOR_PERCENTILE,
+                )
+            },
+            dc_offset: (self.sum / self.count.max(1) as f64) as f32,
+        }
+    }
+}
+
+/// Amplitude in dB, floored for digital silence
+fn amplitude_to_db(amplitude: f64) -> f32 {
+    ((20.0 * amplitude.log10()) as f32).max(crate::constants::analyze::FLOOR_DB)
+}
+
+/// Mean square power in dB, floored for digital silence
+fn power_to_db(power: f64) -> f32 {
+    ((10.0 * power.log10()) as f32).max(crate::constants::analyze::FLOOR_DB)
+}
+
+/// Integrated loudness of 100 ms hops of K-weighted power (BS.1770-4)
+///
+/// Uses 400 ms blocks overlapping by 75%, an absolute gate at -70 LUFS and a
+/// relative gate 10 LU below the absolutely gated loudness.
+fn gated_loudness(hops: &[f64]) -> f32 {
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let blocks: Vec<f64> = hops
+        .windows(4)
+        .map(|w| w.iter().sum::<f64>() / 4.0)
+        .collect();
+
+    let mean_above = |gate: f64| {
+        let gated: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&p| loudness(p) > gate)
+            .collect();
+        (!gated.is_empty()).then(|| gated.iter().sum::<f64>() / gated.len() as f64)
+    };
+
+    let Some(absolute) = mean_above(-70.0) else {
+        return f32::NEG_INFINITY;
+    };
+    let relative_gate = loudness(absolute) - 10.0;
+    mean_above(relative_gate.max(-70.0)).map_or(f32::NEG_INFINITY, |p| loudness(p) as f32)
+}
+
+/// Second-order IIR filter section
+#[derive(Debug, Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    /// Coefficients normalized by `a0`
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b: b.map(|v| v / a[0]),
+            a: [a[1] / a[0], a[2] / a[0]],
+            ..Self::default()
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// The BS.1770 K-weighting filter: a high shelf followed by a high pass
+///
+/// Coefficients are derived for any sample rate as in libebur128, matching
+/// the values tabulated in the standard at 48 kHz.
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    use std::f64::consts::PI;
+
+    let shelf = {
+        let (gain_db, q, frequency) = (3.999843853973347, 0.7071752369554196, 1681.974450955533);
+        let k = (PI * frequency / sample_rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        Biquad::new(
+            [
+                vh + vb * k / q + k * k,
+                2.0 * (k * k - vh),
+                vh - vb * k / q + k * k,
+            ],
+            [
+                1.0 + k / q + k * k,
+                2.0 * (k * k - 1.0),
+                1.0 - k / q + k * k,
+            ],
+        )
+    };
+    let high_pass = {
+        let (q, frequency) = (0.5003270373238773, 38.13547087602444);
+        let k = (PI * frequency / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Biquad::new(
+            [a0, -2.0 * a0, a0],
+            [a0, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+        )
+    };
+    [shelf, high_pass]
+}
+
+/// Taps on each side of the interpolation point
+const TRUE_PEAK_HALF_TAPS: usize = 6;
+/// Oversampling factor for true peak
+const TRUE_PEAK_PHASES: usize = 4;
+
+/// Inter-sample peak detector using windowed-sinc 4x oversampling
+struct TruePeak {
+    coefficients: [[f32; 2 * TRUE_PEAK_HALF_TAPS]; TRUE_PEAK_PHASES],
+    history: [f32; 2 * TRUE_PEAK_HALF_TAPS],
+    peak: f32,
+}
+
+impl TruePeak {
+    fn new() -> Self {
+        let taps = 2 * TRUE_PEAK_HALF_TAPS;
+        let mut coefficients = [[0.0; 2 * TRUE_PEAK_HALF_TAPS]; TRUE_PEAK_PHASES];
+        for (phase, row) in coefficients.iter_mut().enumerate() {
+            // Interpolate between the two middle samples of the history
+            let position =
+                (TRUE_PEAK_HALF_TAPS - 1) as f64 + phase as f64 / TRUE_PEAK_PHASES as f64;
+            for (tap, coefficient) in row.iter_mut().enumerate().take(taps) {
+                let d = position - tap as f64;
+                let sinc = if d == 0.0 {
+                    1.0
+                } else {
+                    (std::f64::consts::PI * d).sin() / (std::f64::consts::PI * d)
+                };
+                let window =
+                    0.5 * (1.0 + (std::f64::consts::PI * d / TRUE_PEAK_HALF_TAPS as f64).cos());
+                *coefficient = (sinc * window) as f32;
+            }
+        }
+        Self {
+            coefficients,
+            history: [0.0; 2 * TRUE_PEAK_HALF_TAPS],
+            peak: 0.0,
+        }
+    }
+
+    fn add(&mut self, sample: f32) {
+        self.history.rotate_left(1);
+        self.history[self.history.len() - 1] = sample;
+        for row in &self.coefficients {
+            let value: f32 = row.iter().zip(&self.history).map(|(c, x)| c * x).sum();
+            self.peak = self.peak.max(value.abs());
+        }
+    }
+
+    /// Flush the samples still waiting to be centred and return the peak
+    fn finish(&mut self) -> f32 {
+        for _ in 0..TRUE_PEAK_HALF_TAPS {
+            self.add(0.0);
+        }
+        self.peak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(
+        frequency: f64,
+        phase: f64,
+        amplitude: f32,
+        sample_rate: u32,
+        seconds: f64,
+    ) -> Vec<f32> {
+        (0..(seconds * sample_rate as f64) as usize)
+            .map(|i| {
+                let t = i as f64 / sample_rate as f64;
+                amplitude * (2.0 * std::f64::consts::PI * frequency * t + phase).sin() as f32
+            })
+            .collect()
+    }
+
+    fn measure(samples: &[f32], sample_rate: u32) -> Measured {
+        let mut meter = ChannelMeter::new(sample_rate);
+        for &sample in samples {
+            meter.add(sample);
+        }
+        meter.finish()
+    }
+
+    #[test]
+    fn test_file_channels_expand_like_device_channels() {
+        let specs: Vec<ChannelSpec> = ["0-1", "3"].iter().map(|s| s.parse().unwrap()).collect();
+        assert_eq!(file_channels(Some(&specs), 4).unwrap(), vec![0, 1, 3]);
+
+        let all = ["all".parse().unwrap()];
+        assert_eq!(file_channels(Some(&all), 2).unwrap(), vec![0, 1]);
+        assert_eq!(file_channels(None, 3).unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_loudness_of_1khz_sine() {
+        // A 1 kHz sine at amplitude 0.1 on one channel measures -23.01 LUFS
+        let measured = measure(&sine(1000.0, 0.0, 0.1, 48000, 5.0), 48000);
+        assert!(
+            (measured.loudness_lufs + 23.01).abs() < 0.1,
+            "{} LUFS",
+            measured.loudness_lufs
+        );
+    }
+
+    #[test]
+    fn test_true_peak_finds_intersample_peak() {
+        // At a quarter of the sample rate with a 45 degree phase every sample is
+        // at 0.707, but the waveform peaks at 1.0
+        let samples = sine(12000.0, std::f64::consts::FRAC_PI_4, 1.0, 48000, 1.0);
+        let measured = measure(&samples, 48000);
+        assert!(
+            measured.true_peak_db.abs() < 0.3,
+            "{} dBTP",
+            measured.true_peak_db
+        );
+    }
+
+    #[test]
+    fn test_noise_floor_and_dc_offset() {
+        let mut samples = vec![0.25f32; 48000];
+        samples.extend(vec![0.001f32; 48000 * 9]);
+        let measured = measure(&samples, 48000);
+        assert!((measured.noise_floor_db + 60.0).abs() < 0.1);
+        assert!((measured.dc_offset - 0.0259).abs() < 0.0001);
+        assert_eq!(gated_loudness(&[]), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_silence_uses_one_floor() {
+        let measured = measure(&vec![0.0f32; 48000], 48000);
+        let floor = crate::constants::analyze::FLOOR_DB;
+        assert_eq!(measured.peak_db, floor);
+        assert_eq!(measured.true_peak_db, floor);
+        assert_eq!(measured.noise_floor_db, floor);
+    }
+
+    #[test]
+    fn test_flags() {
+        let channel = ChannelReport {
+            channel: 0,
+            peak_db: -1.0,
+            true_peak_db: -0.5,
+            rms_db: -20.0,
+            loudness_lufs: -20.0,
+            noise_floor_db: -70.0,
+            dc_offset: 0.0,
+            clips: 0,
+        };
+        let mut report = FileReport {
+            path: PathBuf::from("test.wav"),
+            sample_rate: 48000,
+            duration_s: 1.0,
+            loudness_lufs: -20.0,
+            channels: vec![channel.clone()],
+        };
+        assert!(report.flags().passed());
+
+        report.loudness_lufs = -45.0;
+        report.channels[0].clips = 2;
+        assert_eq!(report.flags().names(), vec!["clipped", "too-quiet"]);
+
+        report.loudness_lufs = f32::NEG_INFINITY;
+        report.channels = vec![ChannelReport {
+            true_peak_db: -80.0,
+            dc_offset: -0.05,
+            ..channel
+        }];
+        assert_eq!(report.flags().names(), vec!["silent", "dc-offset"]);
+    }
+}
@@ -3,32 +3,94 @@
 #![allow(clippy::collapsible_if)]
 
 use crate::audio;
+use crate::channels::{self, ChannelInfo, DeviceGroup};
+use crate::clock::Clock;
 use crate::config::Config;
+use crate::control::{self, Command};
 use crate::error::{AppError, AppResult};
+use crate::events::{EventFormat, EventWriter, LevelEvent};
+use crate::linecheck::LineCheck;
+use crate::metrics;
+use crate::mqtt::MqttPublisher;
+use crate::osc::OscSender;
+use crate::remote;
+use crate::simulate::Simulator;
 use crate::state::{AppState, SharedState};
+use crate::stats::{AverageMethod, StatKind, Statistic};
+use crate::terminal::TerminalGuard;
+use crate::trigger::{self, Trigger, TriggerInfo};
 use crate::ui;
+use crate::web;
 use cpal::traits::StreamTrait;
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
-use ratatui::{Terminal, backend::CrosstermBackend};
-use std::io;
-use std::time::Duration;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
 /// Main application struct
 pub struct App {
     config: Config,
-    terminal: Terminal<CrosstermBackend<std::io::Stdout>>,
+    /// `None` when running headless
+    terminal: Option<TerminalGuard>,
 }
 
 /// Exit codes for the application
 #[derive(Debug, Clone, Copy)]
 pub enum ExitCode {
     Success = 0,
-    UserExit = 1, // User pressed Escape or Ctrl+C
-    Error = 2,    // Actual application error
+    UserExit = 1,    // User pressed Escape or Ctrl+C
+    Error = 2,       // Actual application error
+    DeviceLost = 3,  // Input device disappeared and --reconnect was not given
+    CheckFailed = 4, // An assert condition was not met
+}
+
+/// How the monitoring loop decides when to stop
+#[derive(Debug, Clone, Copy)]
+pub enum Mode {
+    /// Stop when any channel exceeds the threshold; Escape or Ctrl+C cancel
+    Detect,
+    /// Capture for the given number of seconds, or until Enter or Ctrl+C
+    Capture { seconds: Option<f32> },
+    /// Guide the operator through a line check; Escape or Ctrl+C cancel
+    LineCheck,
+}
+
+impl Mode {
+    /// Exit code when the user interrupts with Ctrl+C
+    fn interrupt_exit_code(self) -> ExitCode {
+        match self {
+            Mode::Detect | Mode::LineCheck => ExitCode::UserExit,
+            // Ctrl+C ends a capture early but still reports the results
+            Mode::Capture { .. } => ExitCode::Success,
+        }
+    }
+
+    /// Exit code for a key press, if the key stops this mode
+    fn key_exit_code(self, key_event: &KeyEvent) -> Option<ExitCode> {
+        let ctrl_c = key_event.code == KeyCode::Char('c')
+            && key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match self {
+            Mode::Detect | Mode::LineCheck if ctrl_c || key_event.code == KeyCode::Esc => {
+                Some(ExitCode::UserExit)
+            }
+            Mode::Capture { .. } if ctrl_c || key_event.code == KeyCode::Enter => {
+                Some(ExitCode::Success)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<&AppError> for ExitCode {
+    fn from(err: &AppError) -> Self {
+        match err {
+            AppError::DeviceLost(_) => ExitCode::DeviceLost,
+            _ => ExitCode::Error,
+        }
+    }
 }
 
 /// Result type that includes user exit information
@@ -40,442 +102,794 @@ pub struct RunResult {
     pub exit_code: ExitCode,
 }
 
-impl App {
-    /// Initialize the application with configuration
-    pub fn new_with_config(config: Config) -> AppResult<Self> {
-        // Setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let terminal = Terminal::new(backend)?;
-
-        Ok(App { config, terminal })
-    }
+/// A running source of audio, stopped when dropped
+pub enum Input {
+    #[allow(dead_code)] // Only held to keep the stream running
+    Device(cpal::Stream),
+    Simulated(Simulator),
+}
 
-    /// Run the main application loop
-    pub async fn run(mut self) -> RunResult {
-        // Setup audio
-        let (device, audio_config) =
-            match audio::setup_audio_device(self.config.device_name.clone(), &self.config.channels)
-            {
-                Ok(result) => result,
-                Err(e) => {
-                    return RunResult {
-                        result: Err(e),
-                        exit_code: ExitCode::Error,
-                    };
-                }
-            };
-        let device_name = audio_config.device_name;
+/// One device's stream and the selected channels it feeds
+struct DeviceStream {
+    group: DeviceGroup,
+    stream: Option<Input>,
+    device_name: String,
+    error_tx: mpsc::Sender<cpal::StreamError>,
+    error_rx: mpsc::Receiver<cpal::StreamError>,
+    last_attempt: Instant,
+}
 
-        // Create shared state
-        let shared_state = SharedState::new(self.config.channels.len());
-        let (current_db, smoothed_db, display_db, threshold_reached) = shared_state.audio_refs();
+/// Network services of one monitoring run, stopped when dropped
+///
+/// Dropping also removes the control socket file, so the next run can bind it
+/// even when monitoring ends with an error.
+#[derive(Default)]
+struct Services {
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+    control_socket: Option<PathBuf>,
+}
 
-        // Create app state
-        let mut app_state = AppState::new(
-            device_name,
-            self.config.threshold_db,
-            self.config.channels.len(),
-        );
+impl Services {
+    fn push(&mut self, task: tokio::task::JoinHandle<()>) {
+        self.tasks.push(task);
+    }
+}
 
-        // Build audio stream
-        let audio_callback = audio::create_audio_callback(
-            current_db,
-            smoothed_db,
-            display_db,
-            threshold_reached,
-            self.config.linear_threshold(),
-            &audio_config.selected_channels,
-            audio_config.channels as usize,
-        );
+impl Drop for Services {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        if let Some(path) = &self.control_socket {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
 
-        let config = cpal::StreamConfig {
-            channels: audio_config.channels,
-            sample_rate: cpal::SampleRate(audio_config.sample_rate),
-            buffer_size: crate::constants::audio::BUFFER_SIZE,
-        };
+/// Owns the running input streams and reopens them after a device is lost
+///
+/// Channels qualified by device get one stream per device, all metering
+/// into the same shared state.
+struct StreamSupervisor {
+    streams: Vec<DeviceStream>,
+    clock: Clock,
+    reconnect: bool,
+}
 
-        let stream = match audio::build_audio_stream(&device, &config, audio_callback) {
-            Ok(stream) => stream,
-            Err(e) => {
-                return RunResult {
-                    result: Err(e),
-                    exit_code: ExitCode::Error,
-                };
-            }
+impl StreamSupervisor {
+    /// Open the configured devices and start streaming into the shared state
+    fn start(config: &Config, shared_state: &SharedState) -> AppResult<Self> {
+        // Simulated devices are told apart by name alone
+        let devices = match config.simulate {
+            Some(_) => config.channel_devices.clone(),
+            None => channels::resolve_devices(&config.channel_devices, |device| {
+                audio::input_device_name(device.or(config.device_name.as_deref()))
+            })?,
         };
 
-        if let Err(e) = stream.play() {
-            return RunResult {
-                result: Err(e.into()),
-                exit_code: ExitCode::Error,
-            };
+        let mut streams = Vec::new();
+        for group in channels::group_by_device(&config.channels, &devices) {
+            let (error_tx, error_rx) = mpsc::channel();
+            let device = group.device.clone().or_else(|| config.device_name.clone());
+            let (stream, device_name) =
+                Self::open(config, &group, device, shared_state, error_tx.clone())?;
+            streams.push(DeviceStream {
+                group,
+                stream: Some(stream),
+                device_name,
+                error_tx,
+                error_rx,
+                last_attempt: Instant::now(),
+            });
         }
 
-        // Main UI loop
-        let mut interval = tokio::time::interval(Duration::from_millis(
-            crate::constants::ui::UPDATE_INTERVAL_MS,
-        ));
-        let mut exit_reason = ExitCode::Success;
-
-        loop {
-            // Update state from shared values
-            app_state.update_from_audio(
-                &shared_state.current_db,
-                &shared_state.smoothed_db,
-                &shared_state.display_db,
-                &shared_state.threshold_reached,
-            );
-
-            // Render UI
-            if let Err(e) = self.terminal.draw(|f| {
-                let ui_state = ui::UiState {
-                    device_name: app_state.device_name.clone(),
-                    current_db: app_state.current_db.clone(),
-                    display_db: app_state.display_db.clone(),
-                    threshold_db: app_state.threshold_db,
-                    min_db: self.config.min_db,
-                    status: app_state.status.clone(),
-                };
-                ui::render_ui(f, &ui_state);
-            }) {
-                return RunResult {
-                    result: Err(e.into()),
-                    exit_code: ExitCode::Error,
-                };
-            }
-
-            // Check if threshold reached on any channel
-            if app_state.threshold_reached.iter().any(|&r| r) {
-                exit_reason = ExitCode::Success;
-                break;
-            }
-
-            // Check for keyboard events and signals
-            let mut should_exit = false;
+        // A single simulated input keeps its sample clock; devices run on their own
+        let clock = match streams.as_slice() {
+            [
+                DeviceStream {
+                    stream: Some(Input::Simulated(simulator)),
+                    ..
+                },
+            ] => simulator.clock(),
+            _ => Clock::system(),
+        };
 
-            // Check for Ctrl+C signal
-            tokio::select! {
-                _ = tokio::signal::ctrl_c() => {
-                    should_exit = true;
-                    exit_reason = ExitCode::UserExit;
-                }
-                _ = tokio::time::sleep(Duration::from_millis(1)) => {
-                    // Timeout - check for keyboard events
-                }
-            }
+        Ok(Self {
+            streams,
+            clock,
+            reconnect: config.reconnect,
+        })
+    }
 
-            // Check for keyboard events (Escape to quit)
-            if !should_exit
-                && crossterm::event::poll(Duration::from_millis(0)).unwrap_or(false)
-                && let Ok(Event::Key(key_event)) = crossterm::event::read()
-            {
-                match key_event.code {
-                    KeyCode::Esc => {
-                        should_exit = true;
-                        exit_reason = ExitCode::UserExit;
-                    }
-                    KeyCode::Char('c')
-                        if key_event
-                            .modifiers
-                            .contains(crossterm::event::KeyModifiers::CONTROL) =>
-                    {
-                        should_exit = true;
-                        exit_reason = ExitCode::UserExit;
-                    }
-                    _ => {}
-                }
-            }
+    /// Names of the devices being monitored, in order of their first channel
+    fn device_names(&self) -> Vec<String> {
+        self.streams
+            .iter()
+            .map(|stream| stream.device_name.clone())
+            .collect()
+    }
 
-            if should_exit {
-                break;
+    /// Index into `device_names` for each selected channel
+    fn channel_devices(&self) -> Vec<usize> {
+        let num_channels = self.streams.iter().map(|s| s.group.slots.len()).sum();
+        let mut devices = vec![0; num_channels];
+        for (i, stream) in self.streams.iter().enumerate() {
+            for &slot in &stream.group.slots {
+                devices[slot] = i;
             }
-
-            // Wait for next interval
-            interval.tick().await;
         }
+        devices
+    }
 
-        // Cleanup - ensure graceful exit
-        drop(stream);
-        let _ = self.cleanup(); // Ignore cleanup errors
+    /// Name of the device each selected channel is read from
+    fn channel_device_names(&self) -> Vec<String> {
+        let device_names = self.device_names();
+        self.channel_devices()
+            .into_iter()
+            .map(|device| device_names[device].clone())
+            .collect()
+    }
 
-        RunResult {
-            result: Ok(()),
-            exit_code: exit_reason,
-        }
+    /// Time source matching the input
+    fn clock(&self) -> Clock {
+        self.clock.clone()
     }
 
-    /// Run max monitoring mode
-    pub async fn run_max(&mut self, duration: Option<f32>) -> Result<Vec<f32>, AppError> {
-        // Setup audio
-        let (device, audio_config) =
-            match audio::setup_audio_device(self.config.device_name.clone(), &self.config.channels)
-            {
-                Ok(result) => result,
-                Err(e) => return Err(e),
+    fn open(
+        config: &Config,
+        group: &DeviceGroup,
+        device_name: Option<String>,
+        shared_state: &SharedState,
+        error_tx: mpsc::Sender<cpal::StreamError>,
+    ) -> AppResult<(Input, String)> {
+        if let Some(signal) = &config.simulate {
+            let sample_rate = crate::constants::simulate::SAMPLE_RATE;
+            let total_channels = group.channels.iter().max().map_or(1, |&ch| ch + 1);
+            let audio_callback = audio::create_device_callback(
+                shared_state.clone(),
+                config.ballistics,
+                &group.channels,
+                &group.slots,
+                total_channels,
+                sample_rate,
+            );
+            let simulator = Simulator::start(signal, total_channels, sample_rate, audio_callback)?;
+            let name = match &group.device {
+                Some(device) => format!("{} ({})", signal, device),
+                None => signal.to_string(),
             };
-        let device_name = audio_config.device_name;
-
-        // Create shared state
-        let shared_state = SharedState::new(self.config.channels.len());
-        let (current_db, smoothed_db, display_db, threshold_reached) = shared_state.audio_refs();
-
-        // Create app state with max tracking
-        let mut app_state = AppState::new(
-            device_name,
-            self.config.threshold_db,
-            self.config.channels.len(),
-        );
+            return Ok((Input::Simulated(simulator), name));
+        }
 
-        // Build audio stream
-        let audio_callback = audio::create_audio_callback(
-            current_db,
-            smoothed_db,
-            display_db,
-            threshold_reached,
-            self.config.linear_threshold(),
+        let (device, audio_config) = audio::setup_audio_device(device_name, &group.channels)?;
+        let audio_callback = audio::create_device_callback(
+            shared_state.clone(),
+            config.ballistics,
             &audio_config.selected_channels,
+            &group.slots,
             audio_config.channels as usize,
+            audio_config.sample_rate,
         );
 
-        let config = cpal::StreamConfig {
+        let stream_config = cpal::StreamConfig {
             channels: audio_config.channels,
             sample_rate: cpal::SampleRate(audio_config.sample_rate),
             buffer_size: crate::constants::audio::BUFFER_SIZE,
         };
 
-        let stream = match audio::build_audio_stream(&device, &config, audio_callback) {
-            Ok(stream) => stream,
-            Err(e) => return Err(e),
-        };
-
-        if let Err(e) = stream.play() {
-            return Err(e.into());
-        }
-
-        // Main UI loop with timeout
-        let mut interval = tokio::time::interval(Duration::from_millis(
-            crate::constants::ui::UPDATE_INTERVAL_MS,
-        ));
-        let start_time = tokio::time::Instant::now();
-        let mut max_levels =
-            vec![crate::constants::audio::MIN_DB_LEVEL as f32; self.config.channels.len()];
+        let stream = audio::build_audio_stream(
+            &device,
+            &stream_config,
+            audio::with_capture_clock(audio_callback),
+            error_tx,
+        )?;
+        stream.play()?;
 
-        loop {
-            // Update state from shared values
-            app_state.update_from_audio(
-                &shared_state.current_db,
-                &shared_state.smoothed_db,
-                &shared_state.display_db,
-                &shared_state.threshold_reached,
-            );
+        Ok((Input::Device(stream), audio_config.device_name))
+    }
 
-            // Update max levels
-            for (i, &current) in app_state.current_db.iter().enumerate() {
-                if current > max_levels[i] {
-                    max_levels[i] = current;
+    /// Handle reported stream errors and retry lost devices
+    ///
+    /// Updates `status` with what happened. Returns `AppError::DeviceLost` when
+    /// a device is gone and reconnecting is disabled.
+    fn poll(
+        &mut self,
+        config: &Config,
+        shared_state: &SharedState,
+        status: &mut String,
+    ) -> AppResult<()> {
+        for device in &mut self.streams {
+            let mut lost = None;
+            while let Ok(err) = device.error_rx.try_recv() {
+                tracing::warn!(device = %device.device_name, error = %err, "Audio stream error");
+                shared_state.stream_errors().fetch_add(1, Ordering::Relaxed);
+                match err {
+                    cpal::StreamError::DeviceNotAvailable => lost = Some(err.to_string()),
+                    cpal::StreamError::BackendSpecific { .. } => {
+                        *status = format!("Stream error: {}", err);
+                        if !audio::device_available(&device.device_name) {
+                            lost = Some(err.to_string());
+                        }
+                    }
                 }
             }
 
-            // Render UI
-            if let Err(e) = self.terminal.draw(|f| {
-                let ui_state = ui::UiState {
-                    device_name: app_state.device_name.clone(),
-                    current_db: app_state.current_db.clone(),
-                    display_db: app_state.display_db.clone(),
-                    threshold_db: app_state.threshold_db,
-                    min_db: self.config.min_db,
-                    status: app_state.status.clone(),
-                };
-                ui::render_ui(f, &ui_state);
-            }) {
-                return Err(e.into());
+            if let Some(reason) = lost {
+                tracing::error!(device = %device.device_name, %reason, "Audio device lost");
+                device.stream = None;
+                if !self.reconnect {
+                    return Err(AppError::DeviceLost(format!(
+                        "{} ({})",
+                        device.device_name, reason
+                    )));
+                }
+                *status = format!(
+                    "{} lost: {}. Waiting for it to reappear...",
+                    device.device_name, reason
+                );
+                device.last_attempt = Instant::now();
             }
 
-            // Check for timeout
-            if let Some(dur) = duration
-                && start_time.elapsed() >= Duration::from_secs_f32(dur)
+            if device.stream.is_none()
+                && device.last_attempt.elapsed()
+                    >= Duration::from_millis(crate::constants::audio::RECONNECT_INTERVAL_MS)
             {
-                break;
-            }
-
-            // Check for keyboard events
-            if crossterm::event::poll(Duration::from_millis(0)).unwrap_or(false) {
-                if let Ok(Event::Key(key_event)) = crossterm::event::read() {
-                    match key_event.code {
-                        KeyCode::Enter => break,
-                        KeyCode::Char('c')
-                            if key_event
-                                .modifiers
-                                .contains(crossterm::event::KeyModifiers::CONTROL) =>
-                        {
-                            break;
-                        }
-                        _ => {}
-                    }
+                device.last_attempt = Instant::now();
+                if let Ok((stream, _)) = Self::open(
+                    config,
+                    &device.group,
+                    Some(device.device_name.clone()),
+                    shared_state,
+                    device.error_tx.clone(),
+                ) {
+                    // Errors from the old stream no longer apply
+                    while device.error_rx.try_recv().is_ok() {}
+                    tracing::info!(device = %device.device_name, "Reconnected to audio device");
+                    device.stream = Some(stream);
+                    *status = format!(
+                        "Reconnected to {}. Press Ctrl+C or Escape to quit.",
+                        device.device_name
+                    );
                 }
             }
+        }
 
-            // Wait for next interval
-            interval.tick().await;
+        Ok(())
+    }
+}
+
+impl App {
+    /// Initialize the application with configuration
+    ///
+    /// Falls back to running headless when stdin or stdout is not a
+    /// terminal, e.g. in CI or with redirected output.
+    pub fn new_with_config(config: Config) -> AppResult<Self> {
+        if !(std::io::stdin().is_terminal() && std::io::stdout().is_terminal()) {
+            tracing::info!("No terminal attached, running without the UI");
+            return Ok(Self::new_headless(config));
         }
 
-        // Cleanup
-        drop(stream);
-        let _ = self.cleanup();
+        // Setup terminal; it is restored when the app is dropped
+        let terminal = TerminalGuard::new()?;
 
-        // Return max levels as JSON or something? Wait, the user said "return the max decibel level", but in code, we print them.
+        Ok(App {
+            config,
+            terminal: Some(terminal),
+        })
+    }
 
-        // Actually, since it's a command, we can return the levels, but in main, we print them.
+    /// Initialize the application without the terminal UI
+    ///
+    /// Used by long-running services; status changes are printed to stderr.
+    pub fn new_headless(config: Config) -> Self {
+        App {
+            config,
+            terminal: None,
+        }
+    }
 
-        // But to return, perhaps change to return Vec<f32>
+    /// Run the main application loop
+    pub async fn run(mut self) -> RunResult {
+        self.monitor(Mode::Detect, &mut []).await
+    }
 
-        Ok(max_levels)
+    /// Run max monitoring mode
+    pub async fn run_max(&mut self, duration: Option<f32>) -> Result<Vec<f32>, AppError> {
+        let mut results = self.run_stats(duration, &[StatKind::Max]).await?;
+        Ok(results.remove(0))
     }
 
     /// Run average monitoring mode
-    pub async fn run_average(&mut self, duration: Option<f32>) -> Result<Vec<f32>, AppError> {
-        // Setup audio
-        let (device, audio_config) =
-            match audio::setup_audio_device(self.config.device_name.clone(), &self.config.channels)
-            {
-                Ok(result) => result,
-                Err(e) => return Err(e),
-            };
-        let device_name = audio_config.device_name;
+    pub async fn run_average(
+        &mut self,
+        duration: Option<f32>,
+        method: AverageMethod,
+    ) -> Result<Vec<f32>, AppError> {
+        let mut results = self.run_stats(duration, &[method.stat_kind()]).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Capture audio and compute several statistics in one pass
+    ///
+    /// Returns one vector of per-channel values for each entry in `kinds`.
+    pub async fn run_stats(
+        &mut self,
+        duration: Option<f32>,
+        kinds: &[StatKind],
+    ) -> Result<Vec<Vec<f32>>, AppError> {
+        let statistics = kinds
+            .iter()
+            .map(|kind| kind.build(self.config.channels.len()))
+            .collect();
+        self.run_statistics(duration, statistics).await
+    }
+
+    /// Capture audio and feed it to the given statistics
+    ///
+    /// Returns the per-channel values of each statistic, in order.
+    pub async fn run_statistics(
+        &mut self,
+        duration: Option<f32>,
+        mut statistics: Vec<Box<dyn Statistic>>,
+    ) -> Result<Vec<Vec<f32>>, AppError> {
+        self.monitor(Mode::Capture { seconds: duration }, &mut statistics)
+            .await
+            .result?;
+
+        Ok(statistics.iter().map(|s| s.finish()).collect())
+    }
+
+    /// Create the `--events-out` file
+    fn open_event_log(&self, channels: &[ChannelInfo]) -> AppResult<Option<EventWriter>> {
+        let Some(path) = &self.config.outputs.events_out else {
+            return Ok(None);
+        };
+        let format = self
+            .config
+            .outputs
+            .events_format
+            .unwrap_or_else(|| EventFormat::from_path(path));
+        let writer = EventWriter::create(path, format, channels)?;
+        tracing::info!(path = %path.display(), ?format, "Writing events");
+
+        Ok(Some(writer))
+    }
+
+    /// Shared monitoring loop behind every mode
+    ///
+    /// Streams audio, feeds each snapshot to `statistics`, draws the UI and
+    /// stops according to `mode`.
+    pub async fn monitor(
+        &mut self,
+        mode: Mode,
+        statistics: &mut [Box<dyn Statistic>],
+    ) -> RunResult {
+        self.run_loop(mode, statistics, None).await
+    }
+
+    /// Walk the operator through `line_check` until it finishes or is cancelled
+    pub async fn run_line_check(&mut self, line_check: &mut LineCheck) -> RunResult {
+        self.run_loop(Mode::LineCheck, &mut [], Some(line_check))
+            .await
+    }
+
+    async fn run_loop(
+        &mut self,
+        mode: Mode,
+        statistics: &mut [Box<dyn Statistic>],
+        line_check: Option<&mut LineCheck>,
+    ) -> RunResult {
+        match self.monitor_loop(mode, statistics, line_check).await {
+            Ok(exit_code) => RunResult {
+                result: Ok(()),
+                exit_code,
+            },
+            Err(e) => RunResult {
+                exit_code: ExitCode::from(&e),
+                result: Err(e),
+            },
+        }
+    }
 
+    async fn monitor_loop(
+        &mut self,
+        mode: Mode,
+        statistics: &mut [Box<dyn Statistic>],
+        mut line_check: Option<&mut LineCheck>,
+    ) -> AppResult<ExitCode> {
         // Create shared state
-        let shared_state = SharedState::new(self.config.channels.len());
-        let (current_db, smoothed_db, display_db, threshold_reached) = shared_state.audio_refs();
+        let mut shared_state =
+            SharedState::new(self.config.channels.len(), self.config.linear_threshold());
+
+        // Queue events before streaming so none are missed
+        let outputs = &self.config.outputs;
+        let events = (outputs.events_out.is_some() || outputs.mqtt_broker.is_some())
+            .then(|| shared_state.enable_events());
 
-        // Create app state with average tracking
+        // Setup audio and start streaming
+        let mut supervisor = StreamSupervisor::start(&self.config, &shared_state)?;
+        let channel_info = self.config.channel_info(&supervisor.channel_device_names());
+        let mut event_log = self.open_event_log(&channel_info)?;
+
+        // Create app state
         let mut app_state = AppState::new(
-            device_name,
+            supervisor.device_names().join(" + "),
             self.config.threshold_db,
             self.config.channels.len(),
         );
 
-        // Build audio stream
-        let audio_callback = audio::create_audio_callback(
-            current_db,
-            smoothed_db,
-            display_db,
-            threshold_reached,
-            self.config.linear_threshold(),
-            &audio_config.selected_channels,
-            audio_config.channels as usize,
-        );
-
-        let config = cpal::StreamConfig {
-            channels: audio_config.channels,
-            sample_rate: cpal::SampleRate(audio_config.sample_rate),
-            buffer_size: crate::constants::audio::BUFFER_SIZE,
+        // Send levels and triggers over OSC
+        let mut osc = match &self.config.outputs.osc_target {
+            Some(target) => Some(OscSender::new(
+                target,
+                self.config.outputs.osc_rate,
+                &channel_info,
+            )?),
+            None => None,
         };
-
-        let stream = match audio::build_audio_stream(&device, &config, audio_callback) {
-            Ok(stream) => stream,
-            Err(e) => return Err(e),
+        let mut mqtt = match &self.config.outputs.mqtt_broker {
+            Some(broker) => Some(MqttPublisher::new(
+                broker,
+                &self.config.outputs.mqtt_topic,
+                self.config.outputs.mqtt_interval,
+                &channel_info,
+            )?),
+            None => None,
         };
 
-        if let Err(e) = stream.play() {
-            return Err(e.into());
+        // Latest UI snapshot, shared with services that display it remotely
+        let (ui_tx, ui_rx) = watch::channel(ui::UiState::default());
+
+        // Start network services that read the shared state
+        let mut services = Services::default();
+        if let Some(addr) = self.config.services.metrics_addr {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            self.report(&format!(
+                "Serving Prometheus metrics on http://{}/metrics",
+                addr
+            ));
+            services.push(tokio::spawn(metrics::serve(
+                listener,
+                shared_state.clone(),
+                channel_info.clone(),
+            )));
+        }
+        if let Some(addr) = self.config.services.web_addr {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            self.report(&format!("Serving web meter on http://{}/", addr));
+            services.push(tokio::spawn(web::serve(listener, ui_rx.clone())));
+        }
+
+        if let Some(addr) = self.config.services.stream_addr {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            self.report(&format!("Streaming levels to watch clients on {}", addr));
+            services.push(tokio::spawn(remote::serve(listener, ui_rx.clone())));
         }
 
-        // Main UI loop with timeout
+        // Accept remote control commands, answered below in the loop
+        let (control_tx, mut control_rx) = tokio::sync::mpsc::channel(16);
+        if let Some(path) = &self.config.services.control_socket {
+            #[cfg(unix)]
+            {
+                let listener = control::bind_unix(path)?;
+                services.control_socket = Some(path.clone());
+                self.report(&format!("Accepting control commands on {}", path.display()));
+                services.push(tokio::spawn(control::serve_unix(
+                    listener,
+                    control_tx.clone(),
+                )));
+            }
+            #[cfg(not(unix))]
+            return Err(AppError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "Cannot use {}: Unix domain sockets are not supported here, use --control-addr",
+                    path.display()
+                ),
+            )));
+        }
+        if let Some(addr) = self.config.services.control_addr {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            self.report(&format!("Accepting control commands on {}", addr));
+            services.push(tokio::spawn(control::serve_tcp(
+                listener,
+                control_tx.clone(),
+            )));
+        }
+        drop(control_tx);
+
+        // Main UI loop
         let mut interval = tokio::time::interval(Duration::from_millis(
             crate::constants::ui::UPDATE_INTERVAL_MS,
         ));
-        let start_time = tokio::time::Instant::now();
-        let mut sums: Vec<f32> = vec![0.0; self.config.channels.len()];
-        let mut counts: Vec<u32> = vec![0; self.config.channels.len()];
+        let clock = supervisor.clock();
+        let mut last_timing_log = tokio::time::Instant::now();
+        let mut trigger = Trigger::new(self.config.channels.len(), self.config.trigger.cooldown);
+        let mut last_status = String::new();
+        let device_names = supervisor.device_names();
+        let channel_devices = supervisor.channel_devices();
+        let exit_reason;
+        tracing::info!(?mode, device = %app_state.device_name, "Monitoring started");
 
         loop {
+            // Report stream errors and recover from device loss
+            supervisor.poll(&self.config, &shared_state, &mut app_state.status)?;
+
             // Update state from shared values
-            app_state.update_from_audio(
-                &shared_state.current_db,
-                &shared_state.smoothed_db,
-                &shared_state.display_db,
-                &shared_state.threshold_reached,
-            );
+            app_state.update_from_audio(&shared_state);
 
-            // Accumulate for average
-            for (i, &current) in app_state.current_db.iter().enumerate() {
-                sums[i] += current;
-                counts[i] += 1;
+            // Record level events and publish outputs
+            let level_events = drain_events(events.as_ref());
+            if let Some(writer) = &mut event_log {
+                write_events(writer, &level_events)?;
+            }
+            if let Some(osc) = &mut osc {
+                osc.update(&app_state, trigger.enabled(), clock.now());
+            }
+            if let Some(mqtt) = &mut mqtt {
+                mqtt.update(
+                    &app_state,
+                    trigger.enabled(),
+                    &shared_state,
+                    &level_events,
+                    clock.now(),
+                );
             }
 
-            // Render UI
-            if let Err(e) = self.terminal.draw(|f| {
-                let ui_state = ui::UiState {
-                    device_name: app_state.device_name.clone(),
-                    current_db: app_state.current_db.clone(),
-                    display_db: app_state.display_db.clone(),
-                    threshold_db: app_state.threshold_db,
-                    min_db: self.config.min_db,
-                    status: app_state.status.clone(),
-                };
-                ui::render_ui(f, &ui_state);
-            }) {
-                return Err(e.into());
+            // Periodically log callback timing
+            if last_timing_log.elapsed() >= Duration::from_secs(1) {
+                last_timing_log = tokio::time::Instant::now();
+                log_callback_timing(&shared_state, &supervisor);
             }
 
-            // Check for timeout
-            if let Some(dur) = duration {
-                if start_time.elapsed() >= Duration::from_secs_f32(dur) {
-                    break;
-                }
+            // Accumulate statistics
+            for statistic in statistics.iter_mut() {
+                statistic.update(&app_state);
             }
 
-            // Check for keyboard events
-            if crossterm::event::poll(Duration::from_millis(0)).unwrap_or(false) {
-                if let Ok(Event::Key(key_event)) = crossterm::event::read() {
-                    match key_event.code {
-                        KeyCode::Enter => break,
-                        KeyCode::Char('c')
-                            if key_event
-                                .modifiers
-                                .contains(crossterm::event::KeyModifiers::CONTROL) =>
-                        {
+            // Advance the line check and show its instructions
+            if let Some(line_check) = line_check.as_deref_mut() {
+                line_check.update(&app_state.current_db, clock.now());
+                app_state.status = line_check.status();
+            }
+
+            // Snapshot for the UI and any services showing it
+            let ui_state = ui::UiState {
+                device_name: app_state.device_name.clone(),
+                current_db: app_state.current_db.clone(),
+                display_db: app_state.display_db.clone(),
+                threshold_db: app_state.threshold_db,
+                min_db: self.config.min_db,
+                status: app_state.status.clone(),
+                channels: self.config.channels.clone(),
+                labels: self.config.labels.clone(),
+                devices: device_names.clone(),
+                channel_devices: channel_devices.clone(),
+            };
+
+            // Render UI, or report status changes when running headless
+            if let Some(terminal) = &mut self.terminal {
+                terminal.draw(|f| ui::render_ui(f, &ui_state))?;
+            } else if app_state.status != last_status {
+                last_status.clone_from(&app_state.status);
+                self.report(&app_state.status);
+            }
+            ui_tx.send_replace(ui_state);
+
+            // Check the mode's own stop condition
+            match mode {
+                Mode::Detect => {
+                    if let Some(ch) = trigger.update(&app_state, clock.now()) {
+                        let info = TriggerInfo {
+                            channel: self.config.channels[ch],
+                            label: self.config.label(ch).map(str::to_string),
+                            level_db: app_state.current_db[ch],
+                            threshold_db: app_state.threshold_db,
+                            count: trigger.count(),
+                        };
+                        tracing::info!(
+                            channel = info.channel,
+                            level_db = info.level_db,
+                            threshold_db = info.threshold_db,
+                            count = info.count,
+                            "Threshold reached"
+                        );
+
+                        let repeat = self.config.trigger.repeat;
+                        app_state.status = format!(
+                            "Triggered {} times, last on channel {} at {:.1} dB. Press Ctrl+C or Escape to quit.",
+                            info.count, channel_info[ch], info.level_db
+                        );
+
+                        if let Some(command) = &self.config.trigger.exec {
+                            match trigger::spawn_command(command, &info) {
+                                // Keep metering while repeated commands run
+                                Ok(child) if repeat => {
+                                    tokio::spawn(trigger::wait_for_command(child));
+                                }
+                                Ok(child) => trigger::wait_for_command(child).await,
+                                Err(e) if repeat => {
+                                    tracing::error!(error = %e, "Failed to run trigger command");
+                                    app_state.status = format!("Failed to run command: {}", e);
+                                }
+                                Err(e) => return Err(e.into()),
+                            }
+                        }
+
+                        if !repeat {
+                            exit_reason = ExitCode::Success;
                             break;
                         }
-                        _ => {}
                     }
                 }
+                Mode::Capture { seconds } => {
+                    if let Some(dur) = seconds
+                        && clock.elapsed() >= Duration::from_secs_f32(dur)
+                    {
+                        exit_reason = ExitCode::Success;
+                        break;
+                    }
+                }
+                Mode::LineCheck => {
+                    if line_check.as_deref().is_some_and(LineCheck::finished) {
+                        exit_reason = ExitCode::Success;
+                        break;
+                    }
+                }
+            }
+
+            // Answer remote control commands
+            let mut requested_exit = None;
+            while let Ok(request) = control_rx.try_recv() {
+                let response = match request.command {
+                    Command::Arm | Command::Disarm => {
+                        trigger.set_enabled(request.command == Command::Arm);
+                        control::ok(serde_json::json!({ "armed": trigger.enabled() }))
+                    }
+                    Command::SetThreshold(threshold_db) => {
+                        app_state.threshold_db = threshold_db;
+                        shared_state
+                            .threshold()
+                            .store(crate::smoothing::db_to_amplitude(threshold_db as f32));
+                        control::ok(serde_json::json!({ "threshold_db": threshold_db }))
+                    }
+                    Command::ResetPeaks => {
+                        shared_state.reset_peaks();
+                        control::ok(serde_json::json!({}))
+                    }
+                    Command::GetLevels => control::ok(levels_json(
+                        &app_state,
+                        &shared_state,
+                        &trigger,
+                        &channel_info,
+                    )),
+                    Command::Quit => {
+                        requested_exit = Some(mode.interrupt_exit_code());
+                        control::ok(serde_json::json!({}))
+                    }
+                };
+                tracing::info!(command = ?request.command, "Control command");
+                let _ = request.reply.send(response);
+            }
+
+            // Check for Ctrl+C signal
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    requested_exit = Some(mode.interrupt_exit_code());
+                }
+                _ = tokio::time::sleep(Duration::from_millis(1)) => {
+                    // Timeout - check for keyboard events
+                }
+            }
+
+            // Check for keyboard events
+            if requested_exit.is_none()
+                && self.terminal.is_some()
+                && crossterm::event::poll(Duration::from_millis(0)).unwrap_or(false)
+                && let Ok(Event::Key(key_event)) = crossterm::event::read()
+            {
+                requested_exit = mode.key_exit_code(&key_event);
+                if key_event.code == KeyCode::Char('s')
+                    && let Some(line_check) = line_check.as_deref_mut()
+                {
+                    line_check.skip(clock.now());
+                }
+            }
+
+            if let Some(code) = requested_exit {
+                exit_reason = code;
+                break;
             }
 
             // Wait for next interval
             interval.tick().await;
         }
 
-        // Cleanup
-        drop(stream);
-        let _ = self.cleanup();
-
-        // Calculate averages
-        let mut averages = Vec::new();
-        for (i, &sum) in sums.iter().enumerate() {
-            let avg = if counts[i] > 0 {
-                sum / counts[i] as f32
-            } else {
-                0.0
-            };
-            averages.push(avg);
+        // Stop the stream and services; the terminal is restored when the app is dropped
+        drop(supervisor);
+        drop(services);
+        if let Some(writer) = &mut event_log {
+            write_events(writer, &drain_events(events.as_ref()))?;
+        }
+        if let Some(mqtt) = mqtt {
+            mqtt.shutdown().await;
         }
+        tracing::info!(exit_code = ?exit_reason, "Monitoring stopped");
 
-        Ok(averages)
+        Ok(exit_reason)
     }
 
-    /// Clean up terminal state
-    fn cleanup(&mut self) -> AppResult<()> {
-        disable_raw_mode()?;
-        execute!(
-            self.terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
-        self.terminal.show_cursor()?;
-        Ok(())
+    /// Print a status line when running without the terminal UI
+    fn report(&self, message: &str) {
+        if self.terminal.is_none() {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+/// Current levels and trigger state for the control API
+fn levels_json(
+    app_state: &AppState,
+    shared_state: &SharedState,
+    trigger: &Trigger,
+    channels: &[ChannelInfo],
+) -> serde_json::Value {
+    let channels: Vec<_> = channels
+        .iter()
+        .zip(shared_state.channels())
+        .enumerate()
+        .map(|(i, (info, levels))| {
+            let mut entry = serde_json::json!({
+                "device": info.device,
+                "channel": info.channel,
+                "level_db": app_state.current_db[i],
+                "display_db": app_state.display_db[i],
+                "peak_db": levels.peak_db.load(),
+                "rms_db": levels.rms_db.load(),
+                "triggers": levels.trigger_count.load(Ordering::Relaxed),
+                "clips": levels.clip_count.load(Ordering::Relaxed),
+            });
+            if let Some(label) = &info.label {
+                entry["label"] = label.as_str().into();
+            }
+            entry
+        })
+        .collect();
+
+    serde_json::json!({
+        "device": app_state.device_name,
+        "armed": trigger.enabled(),
+        "threshold_db": app_state.threshold_db,
+        "channels": channels,
+    })
+}
+
+/// Take the level events queued since the last call
+fn drain_events(events: Option<&Receiver<LevelEvent>>) -> Vec<LevelEvent> {
+    events.map(|rx| rx.try_iter().collect()).unwrap_or_default()
+}
+
+/// Write level events to the event log
+fn write_events(writer: &mut EventWriter, events: &[LevelEvent]) -> AppResult<()> {
+    for event in events {
+        writer.write(event)?;
+    }
+    writer.flush()
+}
+
+/// Log how regularly each device's audio callbacks are arriving
+fn log_callback_timing(shared_state: &SharedState, supervisor: &StreamSupervisor) {
+    for stream in &supervisor.streams {
+        let Some(&slot) = stream.group.slots.first() else {
+            continue;
+        };
+        let timing = &shared_state.timing()[slot];
+        tracing::debug!(
+            device = %stream.device_name,
+            callbacks = timing.count.load(Ordering::Relaxed),
+            frames = timing.frames.load(Ordering::Relaxed),
+            max_interval_us = timing.max_interval_us.swap(0, Ordering::Relaxed),
+            "Audio callback timing"
+        );
     }
 }
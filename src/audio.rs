@@ -1,8 +1,13 @@
 //! Audio device handling and stream processing
 
 use crate::error::{AppError, AppResult};
+use crate::events::{EventDetector, EventKind, EventLimits, LevelEvent};
+use crate::smoothing::{self, Ballistics};
+use crate::state::SharedState;
 use cpal::traits::{DeviceTrait, HostTrait};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
 
 /// Audio configuration and device information
 pub struct AudioConfig {
@@ -17,20 +22,78 @@ pub fn setup_audio_device(
     device_name: Option<String>,
     channels: &[usize],
 ) -> AppResult<(cpal::Device, AudioConfig)> {
-    // Setup audio
+    let device = input_device(device_name.as_deref())?;
+    let audio_config = device_config(&device, channels)?;
+    Ok((device, audio_config))
+}
+
+/// Full name of the named or default input device
+pub fn input_device_name(device_name: Option<&str>) -> AppResult<String> {
+    Ok(input_device(device_name)?.name()?)
+}
+
+/// The named input device, or the default one
+fn input_device(device_name: Option<&str>) -> AppResult<cpal::Device> {
     let host = cpal::default_host();
+    match device_name {
+        Some(name) => find_input_device(&host, name),
+        None => host
+            .default_input_device()
+            .ok_or_else(|| AppError::AudioDevice("No default input device available".to_string())),
+    }
+}
+
+/// Number of input channels on the named or default device
+pub fn input_channel_count(device_name: Option<String>) -> AppResult<usize> {
+    let (_, audio_config) = setup_audio_device(device_name, &[])?;
+    Ok(audio_config.channels as usize)
+}
 
-    // Get input device
-    let device = if let Some(name) = device_name {
-        host.input_devices()?
-            .find(|d| d.name().map(|n| n == name).unwrap_or(false))
-            .ok_or_else(|| AppError::AudioDevice("Specified device not found".to_string()))?
-    } else {
-        host.default_input_device()
-            .ok_or_else(|| AppError::AudioDevice("No default input device available".to_string()))?
+/// Find an input device by its exact name, or else by a unique part of it
+///
+/// Parts match ignoring case, spaces and punctuation, so `builtin` finds
+/// "Built-in Microphone".
+fn find_input_device(host: &cpal::Host, name: &str) -> AppResult<cpal::Device> {
+    let devices: Vec<(cpal::Device, String)> = host
+        .input_devices()?
+        .filter_map(|d| d.name().ok().map(|n| (d, n)))
+        .collect();
+    if let Some(index) = devices.iter().position(|(_, n)| n == name) {
+        return Ok(devices.into_iter().nth(index).map(|(d, _)| d).unwrap());
+    }
+
+    let normalize = |text: &str| -> String {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
     };
+    let wanted = normalize(name);
+    let mut matches: Vec<(cpal::Device, String)> = devices
+        .into_iter()
+        .filter(|(_, n)| !wanted.is_empty() && normalize(n).contains(&wanted))
+        .collect();
+    match matches.len() {
+        0 => Err(AppError::AudioDevice(format!(
+            "Specified device not found: {}",
+            name
+        ))),
+        1 => Ok(matches.remove(0).0),
+        _ => {
+            let names: Vec<&str> = matches.iter().map(|(_, n)| n.as_str()).collect();
+            Err(AppError::AudioDevice(format!(
+                "{:?} matches several devices: {}",
+                name,
+                names.join(", ")
+            )))
+        }
+    }
+}
 
+/// Choose a stream config for `device` and check that it has `channels`
+pub fn device_config(device: &cpal::Device, channels: &[usize]) -> AppResult<AudioConfig> {
     let device_name = device.name()?;
+    tracing::info!(device = %device_name, "Selected input device");
 
     // Get supported input configs and determine sample rate and channels from device
     let mut supported_configs = device.supported_input_configs()?;
@@ -50,6 +113,7 @@ pub fn setup_audio_device(
     let max_supported_channels = config_range.channels() as usize;
     for &ch in channels {
         if ch >= max_supported_channels {
+            tracing::error!(channel = ch, max_supported_channels, "Unsupported channel");
             return Err(AppError::AudioDevice(format!(
                 "Channel {} not supported by device config (max {})",
                 ch,
@@ -58,6 +122,14 @@ pub fn setup_audio_device(
         }
     }
 
+    tracing::info!(
+        sample_rate,
+        device_channels = config_range.channels(),
+        selected_channels = ?channels,
+        sample_format = ?config_range.sample_format(),
+        "Chosen stream config"
+    );
+
     let audio_config = AudioConfig {
         device_name,
         sample_rate,
@@ -65,14 +137,26 @@ pub fn setup_audio_device(
         selected_channels: channels.to_vec(),
     };
 
-    Ok((device, audio_config))
+    Ok(audio_config)
+}
+
+/// Check whether an input device with the given name is currently present
+pub fn device_available(device_name: &str) -> bool {
+    cpal::default_host()
+        .input_devices()
+        .map(|mut devices| devices.any(|d| d.name().map(|n| n == device_name).unwrap_or(false)))
+        .unwrap_or(false)
 }
 
 /// Build an audio input stream with the given callback
+///
+/// Stream errors are forwarded to `error_tx` so the UI loop can report them
+/// instead of writing over the terminal.
 pub fn build_audio_stream<F>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
     data_callback: F,
+    error_tx: Sender<cpal::StreamError>,
 ) -> AppResult<cpal::Stream>
 where
     F: FnMut(&[f32], &cpal::InputCallbackInfo) + Send + 'static,
@@ -80,39 +164,170 @@ where
     let stream = device.build_input_stream(
         config,
         data_callback,
-        |err| eprintln!("Audio stream error: {}", err),
+        move |err| {
+            let _ = error_tx.send(err);
+        },
         None,
     )?;
 
     Ok(stream)
 }
 
+/// Adapt an audio callback to cpal, timing buffers by the backend's capture clock
+pub fn with_capture_clock<F>(mut callback: F) -> impl FnMut(&[f32], &cpal::InputCallbackInfo)
+where
+    F: FnMut(&[f32], Option<Duration>) + Send + 'static,
+{
+    let mut first_capture: Option<cpal::StreamInstant> = None;
+    move |data: &[f32], info: &cpal::InputCallbackInfo| {
+        let capture = info.timestamp().capture;
+        let first = *first_capture.get_or_insert(capture);
+        callback(data, capture.duration_since(&first));
+    }
+}
+
 /// Audio processing callback that updates shared state
+///
+/// Called with interleaved samples and the capture time of the first frame
+/// relative to the first buffer, when the input knows it. Runs on the
+/// real-time audio thread, so it only touches atomics and never allocates.
 pub fn create_audio_callback(
-    current_db: Arc<Mutex<Vec<f32>>>,
-    smoothed_db: Arc<Mutex<Vec<f32>>>,
-    display_db: Arc<Mutex<Vec<f32>>>,
-    threshold_reached: Arc<Mutex<Vec<bool>>>,
-    linear_threshold: f32,
+    shared_state: SharedState,
+    ballistics: Ballistics,
     selected_channels: &[usize],
     total_channels: usize,
-) -> impl FnMut(&[f32], &cpal::InputCallbackInfo) + Send + 'static {
+    sample_rate: u32,
+) -> impl FnMut(&[f32], Option<Duration>) + Send + use<> {
+    let slots: Vec<usize> = (0..selected_channels.len()).collect();
+    create_device_callback(
+        shared_state,
+        ballistics,
+        selected_channels,
+        &slots,
+        total_channels,
+        sample_rate,
+    )
+}
+
+/// Audio processing callback for one of several devices sharing a state
+///
+/// Like `create_audio_callback`, but `selected_channels[i]` is metered into
+/// channel `slots[i]` of the shared state.
+pub fn create_device_callback(
+    shared_state: SharedState,
+    ballistics: Ballistics,
+    selected_channels: &[usize],
+    slots: &[usize],
+    total_channels: usize,
+    sample_rate: u32,
+) -> impl FnMut(&[f32], Option<Duration>) + Send + use<> {
     let selected_channels = selected_channels.to_vec();
-    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-        let mut current_db_vec = current_db.lock().unwrap();
-        let mut smoothed_vec = smoothed_db.lock().unwrap();
-        let mut display_vec = display_db.lock().unwrap();
-        let mut threshold_vec = threshold_reached.lock().unwrap();
-
-        for (i, &ch) in selected_channels.iter().enumerate() {
-            // Extract samples for this channel
-            let channel_samples: Vec<f32> = data
-                .iter()
-                .skip(ch)
-                .step_by(total_channels)
-                .map(|&s| s.abs())
-                .collect();
-            let max_sample = channel_samples.iter().fold(0.0f32, |a, &b| a.max(b));
+    let slots = slots.to_vec();
+    let mut last_callback: Option<Instant> = None;
+    let mut limits = EventLimits::new(shared_state.threshold().load());
+    let mut detectors = vec![EventDetector::default(); selected_channels.len()];
+    let mut stream_origin: Option<f64> = None;
+    let mut frames_seen: u64 = 0;
+    // Sign of the last sample outside the zero-crossing dead band, per channel
+    let mut last_signs = vec![0i8; selected_channels.len()];
+    // Devices can run at different rates, so each channel records its own
+    for &slot in &slots {
+        shared_state.channels()[slot]
+            .sample_rate
+            .store(sample_rate, Ordering::Relaxed);
+    }
+    move |data: &[f32], capture: Option<Duration>| {
+        // Record callback timing for diagnostics; logging happens on the UI side
+        let now = Instant::now();
+        let interval_us = last_callback.map(|last| now.duration_since(last).as_micros() as u64);
+        last_callback = Some(now);
+
+        // Time covered by this buffer, so smoothing is independent of buffer size
+        let frames = data.len() / total_channels;
+        let dt = frames as f32 / sample_rate as f32;
+        for &slot in &slots {
+            let timing = &shared_state.timing()[slot];
+            if let Some(interval_us) = interval_us {
+                timing
+                    .max_interval_us
+                    .fetch_max(interval_us, Ordering::Relaxed);
+            }
+            timing.count.fetch_add(1, Ordering::Relaxed);
+            timing.frames.store(frames as u64, Ordering::Relaxed);
+        }
+
+        // Timestamp of the buffer's first sample in seconds since monitoring
+        // started, from the input's capture time when it advances
+        let stream_origin =
+            *stream_origin.get_or_insert_with(|| shared_state.origin().elapsed().as_secs_f64());
+        let block_start = stream_origin
+            + match capture {
+                Some(elapsed) if !elapsed.is_zero() || frames_seen == 0 => elapsed.as_secs_f64(),
+                _ => frames_seen as f64 / sample_rate as f64,
+            };
+        let block_end = block_start + frames as f64 / sample_rate as f64;
+        frames_seen += frames as u64;
+
+        // The threshold can be changed remotely while streaming
+        let linear_threshold = shared_state.threshold().load();
+        limits.threshold = linear_threshold;
+
+        for (i, (&ch, &slot)) in selected_channels.iter().zip(&slots).enumerate() {
+            let levels = &shared_state.channels()[slot];
+            let detector = &mut detectors[i];
+            let mut emit = |kind, time_s, amplitude: f32| {
+                match kind {
+                    EventKind::Threshold => {
+                        levels.trigger_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                    EventKind::Clip => {
+                        levels.clip_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                    EventKind::SilenceStart | EventKind::SilenceEnd => {}
+                }
+                if let Some(events) = shared_state.events() {
+                    let _ = events.try_send(LevelEvent {
+                        kind,
+                        channel: slot,
+                        time_s,
+                        level_db: 20.0 * amplitude.max(f32::MIN_POSITIVE).log10(),
+                    });
+                }
+            };
+
+            // Peak, energy and zero crossings of this channel's samples in the buffer
+            let mut max_sample = 0.0f32;
+            let mut sum_of_squares = 0.0f64;
+            let mut zero_crossings = 0u64;
+            let last_sign = &mut last_signs[i];
+            for (frame, &sample) in data.iter().skip(ch).step_by(total_channels).enumerate() {
+                let amplitude = sample.abs();
+                max_sample = max_sample.max(amplitude);
+                sum_of_squares += (sample as f64).powi(2);
+
+                if amplitude > crate::constants::audio::ZERO_CROSSING_DEAD_BAND {
+                    let sign = if sample > 0.0 { 1 } else { -1 };
+                    if *last_sign != 0 && sign != *last_sign {
+                        zero_crossings += 1;
+                    }
+                    *last_sign = sign;
+                }
+
+                let time_s = block_start + frame as f64 / sample_rate as f64;
+                detector.sample(amplitude, time_s, &limits, &mut emit);
+            }
+            detector.end_block(block_start, block_end, &limits, &mut emit);
+
+            // Accumulate energy for Leq averaging
+            levels
+                .sum_of_squares
+                .store(levels.sum_of_squares.load() + sum_of_squares);
+            levels
+                .sample_count
+                .fetch_add(frames as u64, Ordering::Relaxed);
+            levels
+                .zero_crossings
+                .fetch_add(zero_crossings, Ordering::Relaxed);
 
             let current_db_value = if max_sample > 0.0 {
                 20.0 * max_sample.log10()
@@ -120,21 +335,35 @@ pub fn create_audio_callback(
                 crate::constants::audio::MIN_DB_LEVEL as f32
             };
 
-            // Update current dB
-            current_db_vec[i] = current_db_value;
+            // Update current, peak and RMS dB
+            levels.current_db.store(current_db_value);
+            if current_db_value > levels.peak_db.load() {
+                levels.peak_db.store(current_db_value);
+            }
+            let mean_square = sum_of_squares / frames.max(1) as f64;
+            // Not clamped to the display range so quiet rooms still read true
+            levels.rms_db.store(if mean_square > 0.0 {
+                (10.0 * mean_square.log10() as f32).max(crate::constants::analyze::FLOOR_DB)
+            } else {
+                crate::constants::analyze::FLOOR_DB
+            });
 
             // Apply smoothing
-            let audio_smoothing = crate::constants::smoothing::AUDIO_SMOOTHING_FACTOR;
-            smoothed_vec[i] =
-                smoothed_vec[i] * (1.0 - audio_smoothing) + current_db_value * audio_smoothing;
+            let smoothed = smoothing::smooth_level(levels.smoothed_db.load(), current_db_value, dt);
+            levels.smoothed_db.store(smoothed);
 
-            let display_smoothing = crate::constants::smoothing::DISPLAY_SMOOTHING_FACTOR;
-            display_vec[i] =
-                display_vec[i] * (1.0 - display_smoothing) + smoothed_vec[i] * display_smoothing;
+            // Apply meter ballistics
+            let meter_input = if ballistics.uses_smoothed_input() {
+                smoothed
+            } else {
+                current_db_value
+            };
+            let display = ballistics.step(levels.display_db.load(), meter_input, dt);
+            levels.display_db.store(display);
 
             // Check threshold
             if max_sample > linear_threshold {
-                threshold_vec[i] = true;
+                levels.threshold_reached.store(true, Ordering::Relaxed);
             }
         }
     }
@@ -0,0 +1,363 @@
+//! Channel selection across one or more input devices
+//!
+//! `--channels` entries are hardware channel indices or ranges, optionally
+//! qualified by the device they come from, e.g. `0,1`, `0-7`, `all` or
+//! `usb:0,usb:1,builtin:0`.
+
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Channels named by one `--channels` entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channels {
+    /// Every channel the device has
+    All,
+    /// Hardware channels `first` to `last`, inclusive
+    Range { first: usize, last: usize },
+}
+
+/// One entry of `--channels`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelSpec {
+    /// Device name or part of it; `None` for the `--device` or default device
+    pub device: Option<String>,
+    pub channels: Channels,
+}
+
+impl From<usize> for ChannelSpec {
+    fn from(channel: usize) -> Self {
+        Self {
+            device: None,
+            channels: Channels::Range {
+                first: channel,
+                last: channel,
+            },
+        }
+    }
+}
+
+impl FromStr for ChannelSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (device, channels) = match spec.rsplit_once(':') {
+            Some((device, channels)) => {
+                if device.trim().is_empty() {
+                    return Err(format!("Missing device name in {:?}", spec));
+                }
+                (Some(device.trim().to_string()), channels.trim())
+            }
+            None => (None, spec.trim()),
+        };
+        let index = |value: &str| {
+            value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid channel index in {:?}", spec))
+        };
+        let channels = if channels.eq_ignore_ascii_case("all") {
+            Channels::All
+        } else if let Some((first, last)) = channels.split_once('-') {
+            let (first, last) = (index(first)?, index(last)?);
+            if first > last {
+                return Err(format!("Range runs backwards in {:?}", spec));
+            }
+            Channels::Range { first, last }
+        } else {
+            let channel = index(channels)?;
+            Channels::Range {
+                first: channel,
+                last: channel,
+            }
+        };
+        Ok(Self { device, channels })
+    }
+}
+
+/// Plan files give channels as an index or as a `--channels` entry
+impl<'de> Deserialize<'de> for ChannelSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Entry {
+            Index(usize),
+            Spec(String),
+        }
+        match Entry::deserialize(deserializer)? {
+            Entry::Index(channel) => Ok(channel.into()),
+            Entry::Spec(spec) => spec.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl fmt::Display for ChannelSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(device) = &self.device {
+            write!(f, "{}:", device)?;
+        }
+        match self.channels {
+            Channels::All => write!(f, "all"),
+            Channels::Range { first, last } if first == last => write!(f, "{}", first),
+            Channels::Range { first, last } => write!(f, "{}-{}", first, last),
+        }
+    }
+}
+
+/// `--channels` expanded to one entry per channel
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection {
+    /// Hardware channel index of each selected channel
+    pub channels: Vec<usize>,
+    /// Device named for each channel; `None` for the main device
+    pub devices: Vec<Option<String>>,
+}
+
+/// Expand ranges and `all` in `specs`
+///
+/// `channel_count` gives the number of channels on a device, and is only
+/// asked about devices whose channels are selected with `all`.
+pub fn resolve(
+    specs: &[ChannelSpec],
+    mut channel_count: impl FnMut(Option<&str>) -> AppResult<usize>,
+) -> AppResult<Selection> {
+    let mut selection = Selection::default();
+    for spec in specs {
+        let range = match spec.channels {
+            Channels::All => match channel_count(spec.device.as_deref())? {
+                0 => {
+                    return Err(AppError::AudioDevice(format!(
+                        "No input channels for {}",
+                        spec
+                    )));
+                }
+                count => 0..=count - 1,
+            },
+            Channels::Range { first, last } => first..=last,
+        };
+        for channel in range {
+            selection.channels.push(channel);
+            selection.devices.push(spec.device.clone());
+        }
+    }
+    Ok(selection)
+}
+
+/// Hardware index of a channel followed by its label, e.g. `5 (Snare)`
+pub fn describe(channel: usize, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!("{} ({})", channel, label),
+        None => channel.to_string(),
+    }
+}
+
+/// Where a selected channel comes from and what it is called, for outputs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelInfo {
+    /// Hardware channel index on its device
+    pub channel: usize,
+    /// Device as given in `--channels`; `None` for the main device
+    pub device_spec: Option<String>,
+    /// Name of the device the channel is read from
+    pub device: String,
+    pub label: Option<String>,
+}
+
+impl ChannelInfo {
+    /// Device part of OSC addresses and MQTT topics; `None` for the main device
+    pub fn device_key(&self) -> Option<String> {
+        self.device_spec.as_ref().map(|device| {
+            device
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        '-'
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Path to the channel in OSC addresses and MQTT topics, e.g. `usb/5`
+    ///
+    /// Channels of the main device keep their bare index.
+    pub fn path(&self) -> String {
+        match self.device_key() {
+            Some(device) => format!("{}/{}", device, self.channel),
+            None => self.channel.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ChannelInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(device) = &self.device_spec {
+            write!(f, "{}:", device)?;
+        }
+        write!(f, "{}", describe(self.channel, self.label.as_deref()))
+    }
+}
+
+/// Name the device of each channel by the device it matches
+///
+/// `device_name` looks up the full name of a device given on the command
+/// line, or of the main device for `None`. Specs that name the same device
+/// differently, like `usb` and `USB`, then share one stream.
+pub fn resolve_devices(
+    devices: &[Option<String>],
+    mut device_name: impl FnMut(Option<&str>) -> AppResult<String>,
+) -> AppResult<Vec<Option<String>>> {
+    let mut resolved: Vec<(Option<String>, String)> = Vec::new();
+    devices
+        .iter()
+        .map(|device| {
+            if let Some((_, name)) = resolved.iter().find(|(spec, _)| spec == device) {
+                return Ok(Some(name.clone()));
+            }
+            let name = device_name(device.as_deref())?;
+            resolved.push((device.clone(), name.clone()));
+            Ok(Some(name))
+        })
+        .collect()
+}
+
+/// The selected channels that come from one device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceGroup {
+    /// Device the channels are read from; `None` for the main device
+    pub device: Option<String>,
+    /// Hardware channel indices on the device
+    pub channels: Vec<usize>,
+    /// Position of each of those channels in the whole selection
+    pub slots: Vec<usize>,
+}
+
+/// Group the selection by device, in order of each device's first channel
+pub fn group_by_device(channels: &[usize], devices: &[Option<String>]) -> Vec<DeviceGroup> {
+    let mut groups: Vec<DeviceGroup> = Vec::new();
+    for (slot, &channel) in channels.iter().enumerate() {
+        let device = devices.get(slot).cloned().flatten();
+        match groups.iter_mut().find(|group| group.device == device) {
+            Some(group) => {
+                group.channels.push(channel);
+                group.slots.push(slot);
+            }
+            None => groups.push(DeviceGroup {
+                device,
+                channels: vec![channel],
+                slots: vec![slot],
+            }),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_channel_specs() {
+        assert_eq!("3".parse(), Ok(ChannelSpec::from(3)));
+        assert_eq!(
+            "usb:1-4".parse(),
+            Ok(ChannelSpec {
+                device: Some("usb".to_string()),
+                channels: Channels::Range { first: 1, last: 4 }
+            })
+        );
+        assert_eq!(
+            "ALL".parse::<ChannelSpec>().unwrap().channels,
+            Channels::All
+        );
+        assert_eq!(
+            "hw:CARD=1:0".parse::<ChannelSpec>().unwrap().device,
+            Some("hw:CARD=1".to_string())
+        );
+        assert_eq!(
+            "usb:0-7".parse::<ChannelSpec>().unwrap().to_string(),
+            "usb:0-7"
+        );
+        assert!(":1".parse::<ChannelSpec>().is_err());
+        assert!("usb:x".parse::<ChannelSpec>().is_err());
+        assert!("7-0".parse::<ChannelSpec>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_expands_ranges_and_all() {
+        let specs: Vec<ChannelSpec> = ["0-2", "usb:all", "5"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let selection = resolve(&specs, |device| {
+            assert_eq!(device, Some("usb"));
+            Ok(2)
+        })
+        .unwrap();
+
+        assert_eq!(selection.channels, vec![0, 1, 2, 0, 1, 5]);
+        assert_eq!(selection.devices[3].as_deref(), Some("usb"));
+        assert_eq!(selection.devices[5], None);
+    }
+
+    #[test]
+    fn test_group_by_device() {
+        let specs: Vec<ChannelSpec> = ["usb:0", "builtin:0", "usb:1", "2"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let selection = resolve(&specs, |_| Ok(0)).unwrap();
+        let groups = group_by_device(&selection.channels, &selection.devices);
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].device.as_deref(), Some("usb"));
+        assert_eq!(groups[0].channels, vec![0, 1]);
+        assert_eq!(groups[0].slots, vec![0, 2]);
+        assert_eq!(groups[1].slots, vec![1]);
+        assert_eq!(groups[2].device, None);
+        assert_eq!(groups[2].channels, vec![2]);
+    }
+
+    #[test]
+    fn test_same_device_spelled_differently_is_one_group() {
+        // `usb`, `USB` and the main device all find the USB interface
+        let devices = vec![
+            Some("usb".to_string()),
+            Some("USB".to_string()),
+            None,
+            Some("usb".to_string()),
+        ];
+        let mut lookups = 0;
+        let devices = resolve_devices(&devices, |_| {
+            lookups += 1;
+            Ok("USB Audio".to_string())
+        })
+        .unwrap();
+        assert_eq!(lookups, 3);
+        let groups = group_by_device(&[0, 1, 2, 3], &devices);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].device.as_deref(), Some("USB Audio"));
+        assert_eq!(groups[0].channels, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_channel_info_names_its_device() {
+        let mut info = ChannelInfo {
+            channel: 5,
+            device_spec: None,
+            device: "Built-in Microphone".to_string(),
+            label: Some("Kick".to_string()),
+        };
+        assert_eq!(info.to_string(), "5 (Kick)");
+        assert_eq!(info.path(), "5");
+        assert_eq!(info.device_key(), None);
+
+        info.device_spec = Some("hw:CARD=1".to_string());
+        assert_eq!(info.to_string(), "hw:CARD=1:5 (Kick)");
+        assert_eq!(info.path(), "hw-card-1/5");
+    }
+}
@@ -0,0 +1,398 @@
+//! Pass/fail conditions for the `assert` command
+//!
+//! A capture is measured with ordinary statistics, then every condition is
+//! checked on every channel. Results print as a plain report and can be
+//! written as JUnit XML for CI and production test systems.
+
+use crate::channels::ChannelInfo;
+use crate::config::CheckArgs;
+use crate::error::AppResult;
+use crate::stats::{Clips, Frequency, Max, NoiseFloor, Statistic};
+use serde::Serialize;
+use std::fmt::{self, Write as _};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Inclusive range of levels in dB, parsed from `low,high`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub low: f32,
+    pub high: f32,
+}
+
+impl FromStr for Range {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (low, high) = spec
+            .split_once(',')
+            .ok_or_else(|| format!("Expected low,high in {:?}, e.g. -20,-3", spec))?;
+        let low = parse_number(low, spec)?;
+        let high = parse_number(high, spec)?;
+        if low > high {
+            return Err(format!("Low end is above the high end in {:?}", spec));
+        }
+        Ok(Range { low, high })
+    }
+}
+
+/// Target value with an allowed deviation, parsed from `1000±5`
+///
+/// `+-` and `+/-` are accepted in place of `±` for keyboards without it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub target: f32,
+    pub tolerance: f32,
+}
+
+impl FromStr for Tolerance {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (target, tolerance) = ["±", "+/-", "+-"]
+            .iter()
+            .find_map(|separator| spec.split_once(separator))
+            .ok_or_else(|| format!("Expected target±tolerance in {:?}, e.g. 1000±5", spec))?;
+        let target = parse_number(target, spec)?;
+        let tolerance = parse_number(tolerance, spec)?;
+        if tolerance < 0.0 {
+            return Err(format!("Tolerance must not be negative in {:?}", spec));
+        }
+        Ok(Tolerance { target, tolerance })
+    }
+}
+
+fn parse_number(value: &str, spec: &str) -> Result<f32, String> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| format!("Invalid number in {:?}: {}", spec, value))
+}
+
+/// Per-channel values measured during an assert capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurements {
+    pub peak_db: Vec<f32>,
+    pub noise_db: Vec<f32>,
+    pub clips: Vec<f32>,
+    pub frequency_hz: Vec<f32>,
+}
+
+impl Measurements {
+    /// Statistics to capture, in the order `from_results` expects
+    pub fn statistics(num_channels: usize) -> Vec<Box<dyn Statistic>> {
+        vec![
+            Box::new(Max::new(num_channels)),
+            Box::new(NoiseFloor::new(
+                num_channels,
+                crate::constants::analyze::NOISE_FLOOR_PERCENTILE,
+            )),
+            Box::new(Clips::new(num_channels)),
+            Box::new(Frequency::new(num_channels)),
+        ]
+    }
+
+    /// Collect the finished values of `statistics()`
+    pub fn from_results(results: Vec<Vec<f32>>) -> Self {
+        let mut results = results.into_iter();
+        let mut next = || results.next().unwrap_or_default();
+        Self {
+            peak_db: next(),
+            noise_db: next(),
+            clips: next(),
+            frequency_hz: next(),
+        }
+    }
+}
+
+/// Outcome of one condition on one channel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CheckResult {
+    /// Hardware channel index
+    pub channel: usize,
+    /// Device as given in `--channels`; absent for the main device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Channel label from `--labels`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Short name of the condition, e.g. `peak`
+    pub name: &'static str,
+    pub passed: bool,
+    /// What was measured and what was expected
+    pub message: String,
+}
+
+/// Check every condition in `checks` on every channel
+pub fn evaluate(
+    checks: &CheckArgs,
+    channels: &[ChannelInfo],
+    measurements: &Measurements,
+) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+    for (i, info) in channels.iter().enumerate() {
+        let mut check = |name, passed, message| {
+            results.push(CheckResult {
+                channel: info.channel,
+                device: info.device_spec.clone(),
+                label: info.label.clone(),
+                name,
+                passed,
+                message,
+            })
+        };
+
+        if let Some(range) = checks.peak_between {
+            let peak = measurements.peak_db[i];
+            check(
+                "peak",
+                (range.low..=range.high).contains(&peak),
+                format!(
+                    "{:.1} dB, expected between {:.1} and {:.1} dB",
+                    peak, range.low, range.high
+                ),
+            );
+        }
+        if let Some(limit) = checks.noise_below {
+            let noise = measurements.noise_db[i];
+            check(
+                "noise",
+                noise < limit,
+                format!("{:.1} dB, expected below {:.1} dB", noise, limit),
+            );
+        }
+        if checks.no_clip {
+            let clips = measurements.clips[i] as u64;
+            check(
+                "clip",
+                clips == 0,
+                format!("{} clip events, expected none", clips),
+            );
+        }
+        if let Some(freq) = checks.freq {
+            let frequency = measurements.frequency_hz[i];
+            check(
+                "frequency",
+                (frequency - freq.target).abs() <= freq.tolerance,
+                format!(
+                    "{:.1} Hz, expected {}±{} Hz",
+                    frequency, freq.target, freq.tolerance
+                ),
+            );
+        }
+    }
+    results
+}
+
+/// Readable report of check results, one line per check and a total
+pub struct Report<'a>(pub &'a [CheckResult]);
+
+impl Report<'_> {
+    pub fn passed(&self) -> bool {
+        self.0.iter().all(|result| result.passed)
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in self.0 {
+            let channel = crate::channels::describe(result.channel, result.label.as_deref());
+            let channel = match &result.device {
+                Some(device) => format!("{}:{}", device, channel),
+                None => channel,
+            };
+            writeln!(
+                f,
+                "{} channel {} {:<9} {}",
+                if result.passed { "PASS" } else { "FAIL" },
+                channel,
+                result.name,
+                result.message
+            )?;
+        }
+        let failed = self.0.iter().filter(|result| !result.passed).count();
+        write!(f, "{} checks, {} failed", self.0.len(), failed)
+    }
+}
+
+/// Write `results` as a JUnit XML test suite, one test case per check
+pub fn write_junit(path: &Path, results: &[CheckResult], duration_s: f32) -> AppResult<()> {
+    std::fs::write(path, junit_xml(results, duration_s))?;
+    Ok(())
+}
+
+fn junit_xml(results: &[CheckResult], duration_s: f32) -> String {
+    let failures = results.iter().filter(|result| !result.passed).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    // Writing to a String cannot fail
+    let _ = writeln!(
+        xml,
+        "<testsuite name=\"soundcheck\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        results.len(),
+        failures,
+        duration_s
+    );
+    for result in results {
+        let name = match &result.label {
+            Some(label) => format!("{} ({})", result.name, escape_xml(label)),
+            None => result.name.to_string(),
+        };
+        let classname = match &result.device {
+            Some(device) => format!(
+                "soundcheck.{}.channel{}",
+                escape_xml(device),
+                result.channel
+            ),
+            None => format!("soundcheck.channel{}", result.channel),
+        };
+        let _ = write!(
+            xml,
+            "  <testcase classname=\"{}\" name=\"{}\"",
+            classname, name
+        );
+        if result.passed {
+            let _ = writeln!(xml, "/>");
+        } else {
+            let _ = writeln!(
+                xml,
+                ">\n    <failure message=\"{}\"/>\n  </testcase>",
+                escape_xml(&result.message)
+            );
+        }
+    }
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range_and_tolerance() {
+        assert_eq!(
+            "-20,-3".parse(),
+            Ok(Range {
+                low: -20.0,
+                high: -3.0
+            })
+        );
+        assert!("-3,-20".parse::<Range>().is_err());
+        assert!("-20".parse::<Range>().is_err());
+
+        let expected = Ok(Tolerance {
+            target: 1000.0,
+            tolerance: 5.0,
+        });
+        assert_eq!("1000±5".parse(), expected);
+        assert_eq!("1000+-5".parse(), expected);
+        assert_eq!("1000+/-5".parse(), expected);
+        assert!("1000".parse::<Tolerance>().is_err());
+        assert!("1000±-5".parse::<Tolerance>().is_err());
+    }
+
+    #[test]
+    fn test_evaluate_reports_each_channel() {
+        let checks = CheckArgs {
+            peak_between: Some(Range {
+                low: -20.0,
+                high: -3.0,
+            }),
+            noise_below: None,
+            no_clip: true,
+            freq: Some(Tolerance {
+                target: 1000.0,
+                tolerance: 5.0,
+            }),
+        };
+        let measurements = Measurements {
+            peak_db: vec![-12.0, -1.0],
+            noise_db: vec![-80.0, -80.0],
+            clips: vec![0.0, 3.0],
+            frequency_hz: vec![1000.4, 997.0],
+        };
+
+        let channels: Vec<_> = [(2, "Left"), (3, "Right")]
+            .into_iter()
+            .map(|(channel, label)| ChannelInfo {
+                channel,
+                label: Some(label.to_string()),
+                ..ChannelInfo::default()
+            })
+            .collect();
+        let results = evaluate(&checks, &channels, &measurements);
+        let outcomes: Vec<_> = results
+            .iter()
+            .map(|r| (r.channel, r.name, r.passed))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (2, "peak", true),
+                (2, "clip", true),
+                (2, "frequency", true),
+                (3, "peak", false),
+                (3, "clip", false),
+                (3, "frequency", true),
+            ]
+        );
+        assert!(!Report(&results).passed());
+        assert!(
+            Report(&results)
+                .to_string()
+                .starts_with("PASS channel 2 (Left) peak")
+        );
+        assert!(Report(&results).to_string().ends_with("6 checks, 2 failed"));
+    }
+
+    #[test]
+    fn test_junit_marks_failures() {
+        let results = vec![
+            CheckResult {
+                channel: 0,
+                device: None,
+                label: None,
+                name: "peak",
+                passed: true,
+                message: "-12.0 dB".to_string(),
+            },
+            CheckResult {
+                channel: 0,
+                device: Some("usb".to_string()),
+                label: Some("Mic".to_string()),
+                name: "noise",
+                passed: false,
+                message: "-50.0 dB, expected below -70.0 dB & \"quiet\"".to_string(),
+            },
+        ];
+        let xml = junit_xml(&results, 2.0);
+        assert!(xml.contains("tests=\"2\" failures=\"1\" time=\"2.000\""));
+        assert!(xml.contains("<testcase classname=\"soundcheck.channel0\" name=\"peak\"/>"));
+        assert!(xml.contains("classname=\"soundcheck.usb.channel0\" name=\"noise (Mic)\">"));
+        assert!(
+            Report(&results)
+                .to_string()
+                .contains("FAIL channel usb:0 (Mic) noise")
+        );
+        assert!(
+            xml.contains("message=\"-50.0 dB, expected below -70.0 dB &amp; &quot;quiet&quot;\"")
+        );
+    }
+}
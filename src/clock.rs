@@ -0,0 +1,74 @@
+//! Time source for the monitoring loop
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Where the monitoring loop gets the current time from
+///
+/// Device input follows the wall clock. Simulated input advances the clock
+/// by the samples it has generated, so durations and trigger timing line up
+/// exactly with the generated signal.
+#[derive(Debug, Clone)]
+pub enum Clock {
+    /// Wall-clock time since `start`
+    System { start: Instant },
+    /// Time covered by `frames` samples at `sample_rate`
+    Samples {
+        start: Instant,
+        frames: Arc<AtomicU64>,
+        sample_rate: u32,
+    },
+}
+
+impl Clock {
+    /// Wall clock starting now
+    pub fn system() -> Self {
+        Clock::System {
+            start: Instant::now(),
+        }
+    }
+
+    /// Time since the clock started
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Clock::System { start } => start.elapsed(),
+            Clock::Samples {
+                frames,
+                sample_rate,
+                ..
+            } => {
+                Duration::from_secs_f64(frames.load(Ordering::Relaxed) as f64 / *sample_rate as f64)
+            }
+        }
+    }
+
+    /// Current time as an `Instant`, for code that compares instants
+    pub fn now(&self) -> Instant {
+        match self {
+            Clock::System { .. } => Instant::now(),
+            Clock::Samples { start, .. } => *start + self.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_clock_follows_frames() {
+        let frames = Arc::new(AtomicU64::new(0));
+        let start = Instant::now();
+        let clock = Clock::Samples {
+            start,
+            frames: frames.clone(),
+            sample_rate: 48000,
+        };
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+
+        frames.store(72000, Ordering::Relaxed);
+        assert_eq!(clock.elapsed(), Duration::from_millis(1500));
+        assert_eq!(clock.now(), start + Duration::from_millis(1500));
+    }
+}
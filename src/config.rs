@@ -1,6 +1,17 @@
 //! Configuration parsing and validation
 
+use crate::audio;
+use crate::channels::{self, ChannelInfo, ChannelSpec};
+use crate::checks::{Range, Tolerance};
+use crate::events::EventFormat;
+use crate::simulate::Signal;
+use crate::smoothing::Ballistics;
+use crate::stats::{AverageMethod, StatKind};
+use crate::summary::SummaryFormat;
 use clap::{Parser, Subcommand};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Command line arguments for the soundcheck application
 #[derive(Parser)]
@@ -9,6 +20,10 @@ use clap::{Parser, Subcommand};
 pub struct Args {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Write diagnostic logs to this file (verbosity follows RUST_LOG)
+    #[arg(long, global = true)]
+    pub log_file: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -21,6 +36,24 @@ pub enum Commands {
     Max(MaxArgs),
     /// Monitor audio and report average levels
     Average(AverageArgs),
+    /// Monitor audio and report several statistics from one capture
+    Stats(StatsArgs),
+    /// Capture audio and check it against pass/fail conditions
+    Assert(AssertArgs),
+    /// Run a multi-step test plan from a TOML file
+    Plan(PlanArgs),
+    /// Walk through each channel to find missing and crossed lines
+    Linecheck(LinecheckArgs),
+    /// Meter every channel of every input device at once
+    Scan(ScanArgs),
+    /// Keep monitoring without a UI and expose levels to other programs
+    Serve(ServeArgs),
+    /// Show the meter in a web browser
+    Web(WebArgs),
+    /// Show the meter of a remote `serve --listen` instance
+    Watch(WatchArgs),
+    /// Measure audio files offline
+    Analyze(AnalyzeArgs),
 }
 
 #[derive(Parser)]
@@ -37,9 +70,43 @@ pub struct DetectArgs {
     #[arg(long)]
     pub device: Option<String>,
 
-    /// Audio channels to monitor (comma-separated indices, e.g., "0,1")
-    #[arg(long, value_delimiter = ',', default_values_t = vec![0usize])]
-    pub channels: Vec<usize>,
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to monitor (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Wait for the device to reappear and resume if it is disconnected
+    #[arg(long)]
+    pub reconnect: bool,
+
+    /// Meter ballistics used for the level display
+    #[arg(long, value_enum, default_value_t)]
+    pub ballistics: Ballistics,
+
+    /// Command to run through the shell each time the threshold is reached
+    #[arg(long)]
+    pub exec: Option<String>,
+
+    /// Keep running after a trigger and re-arm once the level falls below the threshold
+    #[arg(long)]
+    pub repeat: bool,
+
+    /// Minimum seconds between triggers
+    #[arg(long, default_value_t = 0.0)]
+    pub cooldown: f32,
+
+    #[command(flatten)]
+    pub outputs: OutputArgs,
+
+    #[command(flatten)]
+    pub control: ControlArgs,
 }
 
 #[derive(Parser)]
@@ -56,13 +123,35 @@ pub struct MaxArgs {
     #[arg(long)]
     pub device: Option<String>,
 
-    /// Audio channels to monitor (comma-separated indices, e.g., "0,1")
-    #[arg(long, value_delimiter = ',', default_values_t = vec![0usize])]
-    pub channels: Vec<usize>,
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to monitor (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Wait for the device to reappear and resume if it is disconnected
+    #[arg(long)]
+    pub reconnect: bool,
+
+    /// Meter ballistics used for the level display
+    #[arg(long, value_enum, default_value_t)]
+    pub ballistics: Ballistics,
 
     /// Output only the integer values without labels
     #[arg(long)]
     pub quiet: bool,
+
+    #[command(flatten)]
+    pub outputs: OutputArgs,
+
+    #[command(flatten)]
+    pub control: ControlArgs,
 }
 
 #[derive(Parser)]
@@ -79,24 +168,501 @@ pub struct AverageArgs {
     #[arg(long)]
     pub device: Option<String>,
 
-    /// Audio channels to monitor (comma-separated indices, e.g., "0,1")
-    #[arg(long, value_delimiter = ',', default_values_t = vec![0usize])]
-    pub channels: Vec<usize>,
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to monitor (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Wait for the device to reappear and resume if it is disconnected
+    #[arg(long)]
+    pub reconnect: bool,
+
+    /// Meter ballistics used for the level display
+    #[arg(long, value_enum, default_value_t)]
+    pub ballistics: Ballistics,
+
+    /// Averaging method: energy (leq), linear peak mean or dB mean
+    #[arg(long, value_enum, default_value_t)]
+    pub method: AverageMethod,
 
     /// Output only the integer values without labels
     #[arg(long)]
     pub quiet: bool,
+
+    #[command(flatten)]
+    pub outputs: OutputArgs,
+
+    #[command(flatten)]
+    pub control: ControlArgs,
+}
+
+#[derive(Parser)]
+pub struct StatsArgs {
+    /// Monitoring duration in seconds (optional, runs until Enter if not specified)
+    #[arg(long)]
+    pub seconds: Option<f32>,
+
+    /// Minimum dB level for display (e.g., -60)
+    #[arg(long, default_value_t = crate::constants::audio::MIN_DB_LEVEL)]
+    pub min_db: i32,
+
+    /// Audio input device name (optional, uses default if not specified)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to monitor (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Wait for the device to reappear and resume if it is disconnected
+    #[arg(long)]
+    pub reconnect: bool,
+
+    /// Meter ballistics used for the level display
+    #[arg(long, value_enum, default_value_t)]
+    pub ballistics: Ballistics,
+
+    /// Statistics to compute (comma-separated, e.g., "min,max,mean,median,p95,stddev")
+    #[arg(
+        long = "stat",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = vec![StatKind::Min, StatKind::Max, StatKind::Mean]
+    )]
+    pub stats: Vec<StatKind>,
+
+    /// Output only the integer values without labels
+    #[arg(long)]
+    pub quiet: bool,
+
+    #[command(flatten)]
+    pub outputs: OutputArgs,
+
+    #[command(flatten)]
+    pub control: ControlArgs,
+}
+
+#[derive(Parser)]
+pub struct AssertArgs {
+    /// Capture duration in seconds
+    #[arg(long)]
+    pub seconds: f32,
+
+    /// Audio input device name (optional, uses default if not specified)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to check (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    #[command(flatten)]
+    pub checks: CheckArgs,
+
+    /// Write the results as JUnit XML to this file
+    #[arg(long)]
+    pub junit: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct PlanArgs {
+    /// Test plan file
+    pub file: PathBuf,
+
+    /// Audio input device name, overriding the plan's
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Use a generated signal instead of an audio device, overriding the plan's
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Write the full report as JSON to this file
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct LinecheckArgs {
+    /// Level that counts as signal on a channel in dB (e.g., -30)
+    #[arg(long, allow_hyphen_values = true, default_value_t = crate::constants::linecheck::DEFAULT_THRESHOLD_DB)]
+    pub threshold: i32,
+
+    /// Audio input device name (optional, uses default if not specified)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to check, in order (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Mark a channel missing after this many seconds without signal
+    #[arg(long)]
+    pub timeout: Option<f32>,
+
+    /// Meter ballistics used for the level display
+    #[arg(long, value_enum, default_value_t)]
+    pub ballistics: Ballistics,
+}
+
+#[derive(Parser)]
+pub struct ScanArgs {
+    /// Level that counts as signal on a channel in dB (e.g., -50)
+    #[arg(long, allow_hyphen_values = true, default_value_t = crate::constants::scan::DEFAULT_THRESHOLD_DB)]
+    pub threshold: i32,
+
+    /// Minimum dB level for display (e.g., -60)
+    #[arg(long, allow_hyphen_values = true, default_value_t = crate::constants::audio::MIN_DB_LEVEL)]
+    pub min_db: i32,
+
+    /// Scan a generated signal instead of the audio devices (e.g. sine:1000:-12)
+    #[arg(long)]
+    pub simulate: Option<Signal>,
+
+    /// Meter ballistics used for the level display
+    #[arg(long, value_enum, default_value_t)]
+    pub ballistics: Ballistics,
+}
+
+impl ScanArgs {
+    /// Check option ranges
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.threshold > 0 || self.threshold < -100 {
+            return Err(format!(
+                "Threshold must be between -100 and 0 dB, got {}",
+                self.threshold
+            )
+            .into());
+        }
+        if self.min_db >= 0 || self.min_db < -100 {
+            return Err(format!(
+                "Minimum dB must be between -100 and 0 dB, got {}",
+                self.min_db
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Conditions checked by `assert`, each applied to every channel
+#[derive(Parser, Debug, Clone, Default)]
+pub struct CheckArgs {
+    /// Peak level must stay within this range in dB (e.g., -20,-3)
+    #[arg(long, allow_hyphen_values = true)]
+    pub peak_between: Option<Range>,
+
+    /// Noise floor must be below this level in dB (e.g., -70)
+    #[arg(long, allow_hyphen_values = true)]
+    pub noise_below: Option<f32>,
+
+    /// Fail if any sample clips
+    #[arg(long)]
+    pub no_clip: bool,
+
+    /// Dominant frequency in Hz with a tolerance (e.g., 1000±5 or 1000+-5)
+    #[arg(long)]
+    pub freq: Option<Tolerance>,
+}
+
+impl CheckArgs {
+    /// Whether no condition was given
+    pub fn is_empty(&self) -> bool {
+        self.peak_between.is_none()
+            && self.noise_below.is_none()
+            && !self.no_clip
+            && self.freq.is_none()
+    }
+}
+
+#[derive(Parser)]
+pub struct ServeArgs {
+    /// Audio threshold in dB used for trigger counts (e.g., -20)
+    #[arg(long, default_value_t = crate::constants::audio::DEFAULT_THRESHOLD_DB)]
+    pub threshold: i32,
+
+    /// Audio input device name (optional, uses default if not specified)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to monitor (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Wait for the device to reappear and resume if it is disconnected
+    #[arg(long)]
+    pub reconnect: bool,
+
+    /// Address for the Prometheus metrics endpoint (e.g., "127.0.0.1:9898")
+    #[arg(long)]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Address to stream level frames to `watch` clients (e.g., "0.0.0.0:7878")
+    #[arg(long)]
+    pub listen: Option<SocketAddr>,
+
+    #[command(flatten)]
+    pub outputs: OutputArgs,
+
+    #[command(flatten)]
+    pub control: ControlArgs,
+}
+
+#[derive(Parser)]
+pub struct WebArgs {
+    /// Address to serve the meter page on (use 0.0.0.0 to allow other devices)
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    pub addr: SocketAddr,
+
+    /// Audio threshold in dB (e.g., 0)
+    #[arg(long, default_value_t = crate::constants::audio::DEFAULT_THRESHOLD_DB)]
+    pub threshold: i32,
+
+    /// Minimum dB level for display (e.g., -60)
+    #[arg(long, default_value_t = crate::constants::audio::MIN_DB_LEVEL)]
+    pub min_db: i32,
+
+    /// Audio input device name (optional, uses default if not specified)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Use a generated signal instead of an audio device (e.g. sine:1000:-12, pink:-20, burst:1,2.5:-6, silence)
+    #[arg(long, conflicts_with = "device")]
+    pub simulate: Option<Signal>,
+
+    /// Audio channels to monitor (comma-separated indices, ranges or "all", optionally prefixed by device, e.g., "0,1", "0-7" or "usb:0,builtin:0")
+    #[arg(long, value_delimiter = ',', default_values_t = vec![ChannelSpec::from(0)])]
+    pub channels: Vec<ChannelSpec>,
+
+    /// Names for the channels, in the same order (e.g., "Kick,Snare")
+    #[arg(long, value_delimiter = ',')]
+    pub labels: Vec<String>,
+
+    /// Wait for the device to reappear and resume if it is disconnected
+    #[arg(long)]
+    pub reconnect: bool,
+
+    /// Meter ballistics used for the level display
+    #[arg(long, value_enum, default_value_t)]
+    pub ballistics: Ballistics,
+
+    #[command(flatten)]
+    pub outputs: OutputArgs,
+
+    #[command(flatten)]
+    pub control: ControlArgs,
 }
 
 #[derive(Parser)]
 pub struct ListArgs {}
 
+#[derive(Parser)]
+pub struct AnalyzeArgs {
+    /// Audio files to analyze
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Channels to analyze (comma-separated indices or ranges, e.g., "0,1" or "0-7"; all if not specified)
+    #[arg(long, value_delimiter = ',')]
+    pub channels: Option<Vec<ChannelSpec>>,
+
+    /// Analyze audio files in directories and their subdirectories
+    #[arg(long, short)]
+    pub recursive: bool,
+
+    /// Write a pass/fail summary of every file to this file
+    #[arg(long)]
+    pub summary: Option<PathBuf>,
+
+    /// Format for --summary (defaults to json for .json files, csv otherwise)
+    #[arg(long, value_enum, requires = "summary")]
+    pub summary_format: Option<SummaryFormat>,
+}
+
+#[derive(Parser)]
+pub struct WatchArgs {
+    /// Address of the remote instance (host:port)
+    pub addr: String,
+}
+
+/// Options for exporting what happens during monitoring
+#[derive(clap::Args, Debug, Clone)]
+pub struct OutputArgs {
+    /// Write threshold, clip and silence events to this file
+    #[arg(long)]
+    pub events_out: Option<PathBuf>,
+
+    /// Format for --events-out (defaults to audacity for .txt files, csv otherwise)
+    #[arg(long, value_enum, requires = "events_out")]
+    pub events_format: Option<EventFormat>,
+
+    /// Send levels and triggers as OSC messages to this host:port
+    #[arg(long)]
+    pub osc_target: Option<String>,
+
+    /// How many times per second to send OSC level messages
+    #[arg(long, default_value_t = 20.0, requires = "osc_target")]
+    pub osc_rate: f32,
+
+    /// Publish levels and activity to this MQTT broker (host or host:port)
+    #[arg(long)]
+    pub mqtt_broker: Option<String>,
+
+    /// Topic prefix for MQTT messages
+    #[arg(long, default_value = "soundcheck", requires = "mqtt_broker")]
+    pub mqtt_topic: String,
+
+    /// Seconds between MQTT level summaries
+    #[arg(long, default_value_t = 1.0, requires = "mqtt_broker")]
+    pub mqtt_interval: f32,
+}
+
+impl Default for OutputArgs {
+    fn default() -> Self {
+        Self {
+            events_out: None,
+            events_format: None,
+            osc_target: None,
+            osc_rate: 20.0,
+            mqtt_broker: None,
+            mqtt_topic: "soundcheck".to_string(),
+            mqtt_interval: 1.0,
+        }
+    }
+}
+
+impl OutputArgs {
+    /// Check option ranges shared by every command
+    fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !(self.osc_rate > 0.0 && self.osc_rate <= 1000.0) {
+            return Err(format!(
+                "OSC rate must be between 0 and 1000 per second, got {}",
+                self.osc_rate
+            )
+            .into());
+        }
+        if self.mqtt_interval.is_nan() || self.mqtt_interval <= 0.0 {
+            return Err(
+                format!("MQTT interval must be positive, got {}", self.mqtt_interval).into(),
+            );
+        }
+        if self.mqtt_topic.is_empty() || self.mqtt_topic.contains(['+', '#']) {
+            return Err(format!(
+                "MQTT topic must be non-empty without wildcards, got {:?}",
+                self.mqtt_topic
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Options for controlling a running instance remotely
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct ControlArgs {
+    /// Accept control commands on this Unix domain socket
+    #[arg(long)]
+    pub control_socket: Option<PathBuf>,
+
+    /// Accept control commands on this TCP address (e.g. 127.0.0.1:7700)
+    #[arg(long)]
+    pub control_addr: Option<SocketAddr>,
+}
+
+/// What happens when the threshold is reached in detect mode
+#[derive(Debug, Clone, Default)]
+pub struct TriggerConfig {
+    /// Shell command to run on each trigger
+    pub exec: Option<String>,
+    /// Keep monitoring after a trigger instead of exiting
+    pub repeat: bool,
+    /// Minimum time between triggers
+    pub cooldown: Duration,
+}
+
+/// Network services started alongside monitoring
+#[derive(Debug, Clone, Default)]
+pub struct ServiceConfig {
+    /// Address for the Prometheus metrics endpoint
+    pub metrics_addr: Option<SocketAddr>,
+    /// Address for the browser meter
+    pub web_addr: Option<SocketAddr>,
+    /// Address for streaming level frames to `watch` clients
+    pub stream_addr: Option<SocketAddr>,
+    /// Unix domain socket for control commands
+    pub control_socket: Option<PathBuf>,
+    /// TCP address for control commands
+    pub control_addr: Option<SocketAddr>,
+}
+
+impl ServiceConfig {
+    /// Services for a command that only offers the control API
+    fn with_control(control: &ControlArgs) -> Self {
+        Self {
+            control_socket: control.control_socket.clone(),
+            control_addr: control.control_addr,
+            ..Self::default()
+        }
+    }
+}
+
 /// Application configuration derived from command line arguments
+#[derive(Debug)]
 pub struct Config {
     pub threshold_db: i32,
     pub min_db: i32,
+    /// Hardware index of each monitored channel
     pub channels: Vec<usize>,
+    /// Device named for each channel; `None` for `device_name` or the default
+    pub channel_devices: Vec<Option<String>>,
+    /// Name of each channel from `--labels`; empty when none were given
+    pub labels: Vec<String>,
     pub device_name: Option<String>,
+    pub simulate: Option<Signal>,
+    pub reconnect: bool,
+    pub ballistics: Ballistics,
+    pub trigger: TriggerConfig,
+    pub outputs: OutputArgs,
+    pub services: ServiceConfig,
 }
 
 impl Config {
@@ -120,12 +686,45 @@ impl Config {
             .into());
         }
 
-        Ok(Config {
+        // Validate cooldown
+        if !(detect_args.cooldown >= 0.0 && detect_args.cooldown.is_finite()) {
+            return Err(format!(
+                "Cooldown must be zero or more seconds, got {}",
+                detect_args.cooldown
+            )
+            .into());
+        }
+
+        detect_args.outputs.validate()?;
+
+        let (selection, labels) = select_channels(
+            &detect_args.channels,
+            &detect_args.labels,
+            detect_args.device.as_deref(),
+            detect_args.simulate.is_some(),
+        )?;
+
+        let config = Config {
             threshold_db: detect_args.threshold,
             min_db: detect_args.min_db,
-            channels: detect_args.channels,
+            channels: selection.channels,
+            channel_devices: selection.devices,
+            labels,
             device_name: detect_args.device,
-        })
+            simulate: detect_args.simulate,
+            reconnect: detect_args.reconnect,
+            ballistics: detect_args.ballistics,
+            trigger: TriggerConfig {
+                exec: detect_args.exec,
+                repeat: detect_args.repeat,
+                cooldown: Duration::from_secs_f32(detect_args.cooldown),
+            },
+            outputs: detect_args.outputs,
+            services: ServiceConfig::with_control(&detect_args.control),
+        };
+        tracing::debug!(?config, "Detect configuration");
+
+        Ok(config)
     }
 
     /// Create configuration from max arguments
@@ -146,12 +745,32 @@ impl Config {
             return Err("Seconds must be positive".into());
         }
 
-        Ok(Config {
+        max_args.outputs.validate()?;
+
+        let (selection, labels) = select_channels(
+            &max_args.channels,
+            &max_args.labels,
+            max_args.device.as_deref(),
+            max_args.simulate.is_some(),
+        )?;
+
+        let config = Config {
             threshold_db: 0, // Dummy value for max monitoring
             min_db: max_args.min_db,
-            channels: max_args.channels.clone(),
+            channels: selection.channels,
+            channel_devices: selection.devices,
+            labels,
             device_name: max_args.device.clone(),
-        })
+            simulate: max_args.simulate.clone(),
+            reconnect: max_args.reconnect,
+            ballistics: max_args.ballistics,
+            trigger: TriggerConfig::default(),
+            outputs: max_args.outputs.clone(),
+            services: ServiceConfig::with_control(&max_args.control),
+        };
+        tracing::debug!(?config, "Max configuration");
+
+        Ok(config)
     }
 
     /// Create configuration from average arguments
@@ -174,18 +793,352 @@ impl Config {
             return Err("Seconds must be positive".into());
         }
 
-        Ok(Config {
+        average_args.outputs.validate()?;
+
+        let (selection, labels) = select_channels(
+            &average_args.channels,
+            &average_args.labels,
+            average_args.device.as_deref(),
+            average_args.simulate.is_some(),
+        )?;
+
+        let config = Config {
             threshold_db: 0, // Dummy value for average monitoring
             min_db: average_args.min_db,
-            channels: average_args.channels.clone(),
+            channels: selection.channels,
+            channel_devices: selection.devices,
+            labels,
             device_name: average_args.device.clone(),
-        })
+            simulate: average_args.simulate.clone(),
+            reconnect: average_args.reconnect,
+            ballistics: average_args.ballistics,
+            trigger: TriggerConfig::default(),
+            outputs: average_args.outputs.clone(),
+            services: ServiceConfig::with_control(&average_args.control),
+        };
+        tracing::debug!(?config, "Average configuration");
+
+        Ok(config)
+    }
+
+    /// Create configuration from stats arguments
+    pub fn from_stats_args(stats_args: &StatsArgs) -> Result<Self, Box<dyn std::error::Error>> {
+        // Validate min_db range
+        if stats_args.min_db >= 0 || stats_args.min_db < -100 {
+            return Err(format!(
+                "Minimum dB must be between -100 and 0 dB, got {}",
+                stats_args.min_db
+            )
+            .into());
+        }
+
+        // Validate seconds if provided
+        if let Some(seconds) = stats_args.seconds
+            && seconds <= 0.0
+        {
+            return Err("Seconds must be positive".into());
+        }
+
+        stats_args.outputs.validate()?;
+
+        let (selection, labels) = select_channels(
+            &stats_args.channels,
+            &stats_args.labels,
+            stats_args.device.as_deref(),
+            stats_args.simulate.is_some(),
+        )?;
+
+        let config = Config {
+            threshold_db: 0, // Dummy value for stats monitoring
+            min_db: stats_args.min_db,
+            channels: selection.channels,
+            channel_devices: selection.devices,
+            labels,
+            device_name: stats_args.device.clone(),
+            simulate: stats_args.simulate.clone(),
+            reconnect: stats_args.reconnect,
+            ballistics: stats_args.ballistics,
+            trigger: TriggerConfig::default(),
+            outputs: stats_args.outputs.clone(),
+            services: ServiceConfig::with_control(&stats_args.control),
+        };
+        tracing::debug!(?config, "Stats configuration");
+
+        Ok(config)
+    }
+
+    /// Create configuration from assert arguments
+    pub fn from_assert_args(assert_args: &AssertArgs) -> Result<Self, Box<dyn std::error::Error>> {
+        if !(assert_args.seconds > 0.0 && assert_args.seconds.is_finite()) {
+            return Err("Seconds must be positive".into());
+        }
+
+        if assert_args.checks.is_empty() {
+            return Err(
+                "Nothing to check: pass --peak-between, --noise-below, --no-clip or --freq".into(),
+            );
+        }
+
+        if let Some(noise_below) = assert_args.checks.noise_below
+            && !noise_below.is_finite()
+        {
+            return Err(format!("Noise level must be a number, got {}", noise_below).into());
+        }
+
+        let config = Config::for_channels(
+            assert_args.device.clone(),
+            assert_args.simulate.clone(),
+            &assert_args.channels,
+            &assert_args.labels,
+        )?;
+        tracing::debug!(?config, "Assert configuration");
+
+        Ok(config)
+    }
+
+    /// Create configuration from linecheck arguments
+    pub fn from_linecheck_args(
+        linecheck_args: &LinecheckArgs,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // Validate threshold range
+        if linecheck_args.threshold > 0 || linecheck_args.threshold < -60 {
+            return Err(format!(
+                "Threshold must be between -60 and 0 dB, got {}",
+                linecheck_args.threshold
+            )
+            .into());
+        }
+
+        if let Some(timeout) = linecheck_args.timeout
+            && !(timeout > 0.0 && timeout.is_finite())
+        {
+            return Err("Timeout must be positive".into());
+        }
+
+        let mut config = Config::for_channels(
+            linecheck_args.device.clone(),
+            linecheck_args.simulate.clone(),
+            &linecheck_args.channels,
+            &linecheck_args.labels,
+        )?;
+        config.threshold_db = linecheck_args.threshold;
+        config.ballistics = linecheck_args.ballistics;
+        tracing::debug!(?config, "Linecheck configuration");
+
+        Ok(config)
+    }
+
+    /// Plain capture of `channels` with default settings and no outputs
+    ///
+    /// Used by commands that measure rather than display, like `assert` and
+    /// `plan`.
+    pub fn for_input(
+        device_name: Option<String>,
+        simulate: Option<Signal>,
+        channels: Vec<usize>,
+    ) -> Self {
+        Config {
+            threshold_db: 0, // Dummy value when not detecting
+            min_db: crate::constants::audio::MIN_DB_LEVEL,
+            channel_devices: vec![None; channels.len()],
+            labels: Vec::new(),
+            channels,
+            device_name,
+            simulate,
+            reconnect: false,
+            ballistics: Ballistics::default(),
+            trigger: TriggerConfig::default(),
+            outputs: OutputArgs::default(),
+            services: ServiceConfig::default(),
+        }
+    }
+
+    /// Plain capture of the channels `specs` select, named by `labels`
+    ///
+    /// Resolves ranges and `all` like `--channels` and checks the labels
+    /// like `--labels`.
+    pub fn for_channels(
+        device_name: Option<String>,
+        simulate: Option<Signal>,
+        specs: &[ChannelSpec],
+        labels: &[String],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (selection, labels) =
+            select_channels(specs, labels, device_name.as_deref(), simulate.is_some())?;
+        let mut config = Config::for_input(device_name, simulate, selection.channels);
+        config.channel_devices = selection.devices;
+        config.labels = labels;
+        Ok(config)
+    }
+
+    /// Create configuration from serve arguments
+    pub fn from_serve_args(serve_args: ServeArgs) -> Result<Self, Box<dyn std::error::Error>> {
+        // Validate threshold range
+        if serve_args.threshold > 0 || serve_args.threshold < -60 {
+            return Err(format!(
+                "Threshold must be between -60 and 0 dB, got {}",
+                serve_args.threshold
+            )
+            .into());
+        }
+
+        // Something has to be served or published
+        if serve_args.metrics_addr.is_none()
+            && serve_args.listen.is_none()
+            && serve_args.control.control_socket.is_none()
+            && serve_args.control.control_addr.is_none()
+            && serve_args.outputs.mqtt_broker.is_none()
+            && serve_args.outputs.osc_target.is_none()
+            && serve_args.outputs.events_out.is_none()
+        {
+            return Err(
+                "Nothing to serve: pass --metrics-addr, --listen, --control-socket, --control-addr, --mqtt-broker, --osc-target or --events-out"
+                    .into(),
+            );
+        }
+
+        serve_args.outputs.validate()?;
+
+        let (selection, labels) = select_channels(
+            &serve_args.channels,
+            &serve_args.labels,
+            serve_args.device.as_deref(),
+            serve_args.simulate.is_some(),
+        )?;
+
+        let config = Config {
+            threshold_db: serve_args.threshold,
+            min_db: crate::constants::audio::MIN_DB_LEVEL,
+            channels: selection.channels,
+            channel_devices: selection.devices,
+            labels,
+            device_name: serve_args.device,
+            simulate: serve_args.simulate,
+            reconnect: serve_args.reconnect,
+            ballistics: Ballistics::default(),
+            trigger: TriggerConfig::default(),
+            outputs: serve_args.outputs,
+            services: ServiceConfig {
+                metrics_addr: serve_args.metrics_addr,
+                stream_addr: serve_args.listen,
+                ..ServiceConfig::with_control(&serve_args.control)
+            },
+        };
+        tracing::debug!(?config, "Serve configuration");
+
+        Ok(config)
+    }
+
+    /// Create configuration from web arguments
+    pub fn from_web_args(web_args: WebArgs) -> Result<Self, Box<dyn std::error::Error>> {
+        // Validate threshold range
+        if web_args.threshold > 0 || web_args.threshold < -60 {
+            return Err(format!(
+                "Threshold must be between -60 and 0 dB, got {}",
+                web_args.threshold
+            )
+            .into());
+        }
+
+        // Validate min_db range
+        if web_args.min_db >= 0 || web_args.min_db < -100 {
+            return Err(format!(
+                "Minimum dB must be between -100 and 0 dB, got {}",
+                web_args.min_db
+            )
+            .into());
+        }
+
+        web_args.outputs.validate()?;
+
+        let (selection, labels) = select_channels(
+            &web_args.channels,
+            &web_args.labels,
+            web_args.device.as_deref(),
+            web_args.simulate.is_some(),
+        )?;
+
+        let config = Config {
+            threshold_db: web_args.threshold,
+            min_db: web_args.min_db,
+            channels: selection.channels,
+            channel_devices: selection.devices,
+            labels,
+            device_name: web_args.device,
+            simulate: web_args.simulate,
+            reconnect: web_args.reconnect,
+            ballistics: web_args.ballistics,
+            trigger: TriggerConfig::default(),
+            outputs: web_args.outputs,
+            services: ServiceConfig {
+                web_addr: Some(web_args.addr),
+                ..ServiceConfig::with_control(&web_args.control)
+            },
+        };
+        tracing::debug!(?config, "Web configuration");
+
+        Ok(config)
     }
 
     /// Convert dB threshold to linear amplitude for audio processing
     pub fn linear_threshold(&self) -> f32 {
         crate::smoothing::db_to_amplitude(self.threshold_db as f32)
     }
+
+    /// Label of the channel at position `i` in the selection, if any
+    pub fn label(&self, i: usize) -> Option<&str> {
+        self.labels.get(i).map(String::as_str)
+    }
+
+    /// Where every channel comes from, given the name of each channel's device
+    pub fn channel_info(&self, devices: &[String]) -> Vec<ChannelInfo> {
+        self.channels
+            .iter()
+            .enumerate()
+            .map(|(i, &channel)| ChannelInfo {
+                channel,
+                device_spec: self.channel_devices.get(i).cloned().flatten(),
+                device: devices.get(i).cloned().unwrap_or_default(),
+                label: self.label(i).map(str::to_string),
+            })
+            .collect()
+    }
+
+    /// Device, hardware index and label of every channel, for printed results
+    pub fn channel_names(&self) -> Vec<String> {
+        self.channel_info(&[])
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+}
+
+/// Expand `--channels` and check that `--labels` names every channel
+///
+/// Channels selected with `all` are counted on their device, or on the
+/// simulated input when `simulate` is set.
+fn select_channels(
+    specs: &[ChannelSpec],
+    labels: &[String],
+    device_name: Option<&str>,
+    simulate: bool,
+) -> Result<(channels::Selection, Vec<String>), Box<dyn std::error::Error>> {
+    let selection = channels::resolve(specs, |device| {
+        if simulate {
+            return Ok(crate::constants::simulate::CHANNELS);
+        }
+        audio::input_channel_count(device.or(device_name).map(str::to_string))
+    })?;
+
+    if !labels.is_empty() && labels.len() != selection.channels.len() {
+        return Err(format!(
+            "Got {} labels for {} channels",
+            labels.len(),
+            selection.channels.len()
+        )
+        .into());
+    }
+    Ok((selection, labels.to_vec()))
 }
 
 #[cfg(test)]
@@ -200,7 +1153,15 @@ mod tests {
             threshold_db: 0,
             min_db: -60,
             channels: vec![0],
+            channel_devices: vec![None],
+            labels: Vec::new(),
             device_name: Some("test_device".to_string()),
+            simulate: None,
+            reconnect: false,
+            ballistics: Ballistics::default(),
+            trigger: TriggerConfig::default(),
+            outputs: OutputArgs::default(),
+            services: ServiceConfig::default(),
         };
 
         assert_eq!(config.threshold_db, 0);
@@ -210,13 +1171,38 @@ mod tests {
         assert!(config.linear_threshold() > 0.0);
     }
 
+    #[test]
+    fn test_serve_accepts_publishing_only() {
+        let serve = |args: &[&str]| {
+            let args = ["serve", "--simulate", "sine:1000:-12"]
+                .iter()
+                .chain(args)
+                .copied();
+            Config::from_serve_args(ServeArgs::try_parse_from(args).unwrap())
+        };
+
+        let config = serve(&["--mqtt-broker", "127.0.0.1:1"]).unwrap();
+        assert_eq!(config.outputs.mqtt_broker.as_deref(), Some("127.0.0.1:1"));
+        let config = serve(&["--osc-target", "127.0.0.1:9000"]).unwrap();
+        assert_eq!(config.outputs.osc_target.as_deref(), Some("127.0.0.1:9000"));
+        assert!(serve(&[]).is_err());
+    }
+
     #[test]
     fn test_db_to_linear_conversion() {
         let config = Config {
             threshold_db: 0,
             min_db: -60,
             device_name: None,
+            simulate: None,
             channels: vec![0],
+            channel_devices: vec![None],
+            labels: Vec::new(),
+            reconnect: false,
+            ballistics: Ballistics::default(),
+            trigger: TriggerConfig::default(),
+            outputs: OutputArgs::default(),
+            services: ServiceConfig::default(),
         };
         // 0 dB should convert to amplitude of 1.0
         assert!((config.linear_threshold() - 1.0).abs() < 0.001);
@@ -225,7 +1211,15 @@ mod tests {
             threshold_db: -20,
             min_db: -60,
             device_name: Some("test_device".to_string()),
+            simulate: None,
             channels: vec![0],
+            channel_devices: vec![None],
+            labels: Vec::new(),
+            reconnect: false,
+            ballistics: Ballistics::default(),
+            trigger: TriggerConfig::default(),
+            outputs: OutputArgs::default(),
+            services: ServiceConfig::default(),
         };
         // -20 dB should convert to amplitude of ~0.1
         assert!((config.linear_threshold() - 0.1).abs() < 0.01);
@@ -8,6 +8,10 @@ pub mod audio {
     pub const DEFAULT_THRESHOLD_DB: i32 = 0;
     /// Buffer size for audio streams
     pub const BUFFER_SIZE: cpal::BufferSize = cpal::BufferSize::Default;
+    /// Delay between attempts to reopen a lost device in milliseconds
+    pub const RECONNECT_INTERVAL_MS: u64 = 500;
+    /// Amplitude a signal must swing past for a zero crossing to count
+    pub const ZERO_CROSSING_DEAD_BAND: f32 = 0.001;
 }
 
 /// UI display constants
@@ -18,10 +22,98 @@ pub mod ui {
     pub const BAR_BORDER_WIDTH: usize = 2;
 }
 
+/// Network service constants
+pub mod net {
+    /// Interval between level updates sent to remote meters in milliseconds
+    pub const STREAM_INTERVAL_MS: u64 = 50;
+}
+
 /// Smoothing algorithm constants
 pub mod smoothing {
-    /// First stage audio smoothing factor (higher = more responsive)
-    pub const AUDIO_SMOOTHING_FACTOR: f32 = 0.4;
-    /// Second stage display smoothing factor (lower = smoother)
-    pub const DISPLAY_SMOOTHING_FACTOR: f32 = 0.15;
+    /// First stage audio smoothing time constant in seconds (lower = more responsive)
+    pub const AUDIO_SMOOTHING_TIME_S: f32 = 0.023;
+    /// Second stage display smoothing time constant in seconds (higher = smoother)
+    pub const DISPLAY_SMOOTHING_TIME_S: f32 = 0.071;
+}
+
+/// Event detection constants
+pub mod events {
+    /// Sample amplitude treated as clipping
+    pub const CLIP_LEVEL: f32 = 0.999;
+    /// Level below which a channel counts as silent, in dB
+    pub const SILENCE_DB: f32 = -50.0;
+    /// How long a channel must stay below the silence level, in seconds
+    pub const SILENCE_HOLD_S: f64 = 2.0;
+    /// Events buffered between the audio thread and the UI loop
+    pub const QUEUE_CAPACITY: usize = 1024;
+}
+
+/// MQTT publishing constants
+pub mod mqtt {
+    /// Broker port used when `--mqtt-broker` has none
+    pub const DEFAULT_PORT: u16 = 1883;
+    /// Keep-alive interval for the broker connection, in seconds
+    pub const KEEP_ALIVE_S: u64 = 10;
+    /// Messages queued for the connection task
+    pub const QUEUE_CAPACITY: usize = 256;
+    /// How long a channel must stay below the threshold to publish OFF, in seconds
+    pub const TRIGGER_HOLD_S: f64 = 1.0;
+    /// How long to wait for the final messages when stopping
+    pub const SHUTDOWN_TIMEOUT_MS: u64 = 1000;
+}
+
+/// Simulated input constants
+pub mod simulate {
+    /// Sample rate of simulated input
+    pub const SAMPLE_RATE: u32 = 48000;
+    /// Channels of the simulated device, as selected by `--channels all`
+    pub const CHANNELS: usize = 2;
+    /// Length of each generated buffer, in milliseconds
+    pub const BUFFER_MS: u64 = 10;
+    /// Signal level when a `--simulate` spec gives none, in dBFS
+    pub const DEFAULT_LEVEL_DB: f32 = -12.0;
+    /// Length of each scheduled burst, in seconds
+    pub const BURST_S: f64 = 0.1;
+    /// Tone used for bursts, in Hz
+    pub const BURST_FREQUENCY_HZ: f64 = 1000.0;
+    /// Seed for the noise generator so every run is identical
+    pub const NOISE_SEED: u32 = 0x2545_f491;
+}
+
+/// Offline file analysis constants
+pub mod analyze {
+    /// Frames decoded and metered at a time
+    pub const BLOCK_FRAMES: usize = 1024;
+    /// Lowest level reported for digital silence, in dB
+    pub const FLOOR_DB: f32 = -120.0;
+    /// Percentile of 100 ms levels reported as the noise floor
+    pub const NOISE_FLOOR_PERCENTILE: f32 = 10.0;
+    /// DC offset, as a fraction of full scale, above which a file is flagged
+    pub const DC_OFFSET_LIMIT: f32 = 0.01;
+    /// Integrated loudness below which a file is flagged as too quiet, in LUFS
+    pub const TOO_QUIET_LUFS: f32 = -30.0;
+    /// File extensions picked up when searching directories
+    pub const EXTENSIONS: &[&str] = &[
+        "wav", "wave", "flac", "mp3", "ogg", "oga", "m4a", "mp4", "aac",
+    ];
+}
+
+/// Line check constants
+pub mod linecheck {
+    /// Level a channel must reach to count as having signal, in dB
+    pub const DEFAULT_THRESHOLD_DB: i32 = -30;
+    /// How long signal must stay on the wrong channel before it counts as swapped
+    pub const SWAP_HOLD_S: f32 = 0.3;
+    /// Pause before listening for the next channel, so the last one can ring out
+    pub const SETTLE_S: f32 = 1.0;
+}
+
+/// Device scan constants
+pub mod scan {
+    /// Level above which a channel is highlighted as having signal, in dB
+    pub const DEFAULT_THRESHOLD_DB: i32 = -50;
+    /// How long a channel stays highlighted after its signal drops, in seconds
+    pub const ACTIVE_HOLD_S: f32 = 2.0;
+    /// Width of each channel's meter in characters
+    pub const BAR_WIDTH: usize = 12;
 }
@@ -0,0 +1,234 @@
+//! Remote control of a running instance
+//!
+//! Clients send one command per line over a Unix domain socket or TCP and
+//! get one JSON object per line back. Commands are handed to the monitoring
+//! loop, which owns the state they act on.
+
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+
+/// A command understood by the control API
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// Let threshold crossings fire triggers
+    Arm,
+    /// Keep metering but ignore threshold crossings
+    Disarm,
+    /// Change the threshold in dB
+    SetThreshold(i32),
+    /// Clear the held peak levels
+    ResetPeaks,
+    /// Report the current levels
+    GetLevels,
+    /// Stop monitoring as if the user had quit
+    Quit,
+}
+
+impl Command {
+    /// Parse a command line such as `set-threshold -20`
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("Empty command")?;
+        let command = match name {
+            "arm" => Command::Arm,
+            "disarm" => Command::Disarm,
+            "set-threshold" => {
+                let value = words.next().ok_or("set-threshold needs a dB value")?;
+                let threshold: i32 = value
+                    .parse()
+                    .map_err(|_| format!("Invalid threshold: {}", value))?;
+                let min_db = crate::constants::audio::MIN_DB_LEVEL;
+                if !(min_db..=0).contains(&threshold) {
+                    return Err(format!(
+                        "Threshold must be between {} and 0 dB, got {}",
+                        min_db, threshold
+                    ));
+                }
+                Command::SetThreshold(threshold)
+            }
+            "reset-peaks" => Command::ResetPeaks,
+            "get-levels" => Command::GetLevels,
+            "quit" => Command::Quit,
+            _ => return Err(format!("Unknown command: {}", name)),
+        };
+        if words.next().is_some() {
+            return Err(format!("Too many arguments for {}", name));
+        }
+        Ok(command)
+    }
+}
+
+/// A command waiting for the monitoring loop to answer it
+pub struct Request {
+    pub command: Command,
+    pub reply: oneshot::Sender<Value>,
+}
+
+/// Successful response with extra fields from `body`
+pub fn ok(body: Value) -> Value {
+    let mut response = json!({ "ok": true });
+    if let (Some(response), Value::Object(body)) = (response.as_object_mut(), body) {
+        response.extend(body);
+    }
+    response
+}
+
+/// Failed response carrying `message`
+pub fn error(message: &str) -> Value {
+    json!({ "ok": false, "error": message })
+}
+
+/// Accept control connections over TCP until the task is dropped
+pub async fn serve_tcp(listener: TcpListener, requests: mpsc::Sender<Request>) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                crate::http::accept_failed("control", e).await;
+                continue;
+            }
+        };
+        tracing::info!(%peer, "Control client connected");
+        tokio::spawn(handle_connection(stream, requests.clone()));
+    }
+}
+
+/// Accept control connections on a Unix domain socket until the task is dropped
+#[cfg(unix)]
+pub async fn serve_unix(listener: tokio::net::UnixListener, requests: mpsc::Sender<Request>) {
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                crate::http::accept_failed("control", e).await;
+                continue;
+            }
+        };
+        tracing::info!("Control client connected");
+        tokio::spawn(handle_connection(stream, requests.clone()));
+    }
+}
+
+/// Bind a Unix domain socket, replacing one left behind by an earlier run
+///
+/// A socket that still accepts connections belongs to a running instance
+/// and is left alone.
+#[cfg(unix)]
+pub fn bind_unix(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        match std::os::unix::net::UnixStream::connect(path) {
+            Ok(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AddrInUse,
+                    format!("{} is already in use by another instance", path.display()),
+                ));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                std::fs::remove_file(path)?;
+            }
+            Err(_) => {}
+        }
+    }
+    tokio::net::UnixListener::bind(path)
+}
+
+/// Answer commands from one client until it disconnects or monitoring stops
+async fn handle_connection<S>(stream: S, requests: mpsc::Sender<Request>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match Command::parse(&line) {
+            Ok(command) => {
+                let (reply, response) = oneshot::channel();
+                if requests.send(Request { command, reply }).await.is_err() {
+                    return;
+                }
+                match response.await {
+                    Ok(response) => response,
+                    Err(_) => return,
+                }
+            }
+            Err(message) => error(&message),
+        };
+
+        let mut line = response.to_string();
+        line.push('\n');
+        if writer.write_all(line.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(Command::parse("arm"), Ok(Command::Arm));
+        assert_eq!(
+            Command::parse("  set-threshold -20 "),
+            Ok(Command::SetThreshold(-20))
+        );
+        assert!(Command::parse("set-threshold 6").is_err());
+        assert!(Command::parse("set-threshold").is_err());
+        assert!(Command::parse("quit now").is_err());
+        assert!(Command::parse("dance").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bind_unix_keeps_live_socket() {
+        let path =
+            std::env::temp_dir().join(format!("soundcheck-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let listener = bind_unix(&path).unwrap();
+        let err = bind_unix(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+        // Once nobody listens, the stale socket is replaced
+        drop(listener);
+        let listener = bind_unix(&path).unwrap();
+        drop(listener);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tcp_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (requests, mut commands) = mpsc::channel(1);
+        tokio::spawn(serve_tcp(listener, requests));
+        tokio::spawn(async move {
+            while let Some(request) = commands.recv().await {
+                let _ = request
+                    .reply
+                    .send(ok(json!({ "command": format!("{:?}", request.command) })));
+            }
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"reset-peaks\nbogus\n").await.unwrap();
+        let mut lines = BufReader::new(stream).lines();
+
+        let reply: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reply, json!({ "ok": true, "command": "ResetPeaks" }));
+        let reply: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reply["ok"], json!(false));
+    }
+}
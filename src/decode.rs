@@ -0,0 +1,252 @@
+//! Reading audio files as blocks of interleaved samples
+//!
+//! WAV files are read directly; FLAC, MP3, Ogg Vorbis and AAC/MP4 are
+//! decoded with Symphonia.
+
+use crate::error::{AppError, AppResult};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use symphonia::core::audio::{SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{CODEC_TYPE_NULL, Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// An audio file opened for reading
+pub struct AudioFile {
+    sample_rate: u32,
+    channels: usize,
+    reader: Reader,
+}
+
+enum Reader {
+    Wav(hound::WavReader<BufReader<File>>),
+    Decoded(Box<SymphoniaReader>),
+}
+
+/// A compressed stream and the samples decoded from it but not yet read
+struct SymphoniaReader {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    pending: Vec<f32>,
+    finished: bool,
+    /// Rate and layout of the last decoded packet
+    spec: Option<SignalSpec>,
+}
+
+impl AudioFile {
+    /// Open `path` and read its format
+    pub fn open(path: &Path) -> AppResult<Self> {
+        let is_wav = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav") || ext.eq_ignore_ascii_case("wave"));
+        if !is_wav {
+            return Self::open_decoded(path);
+        }
+
+        let reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        Ok(Self {
+            sample_rate: spec.sample_rate,
+            channels: spec.channels as usize,
+            reader: Reader::Wav(reader),
+        })
+    }
+
+    /// Open `path` with Symphonia, guessing the format from its contents
+    fn open_decoded(path: &Path) -> AppResult<Self> {
+        let source = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+            hint.with_extension(ext);
+        }
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?;
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| AppError::AudioFile("No audio track found".to_string()))?;
+        let params = track.codec_params.clone();
+        let track_id = track.id;
+        let decoder = symphonia::default::get_codecs().make(&params, &DecoderOptions::default())?;
+        let mut reader = SymphoniaReader {
+            format,
+            decoder,
+            track_id,
+            pending: Vec::new(),
+            finished: false,
+            spec: None,
+        };
+
+        // ADTS AAC and some MP3 streams only give their format with the first
+        // decoded packet, whose samples stay pending for the first read
+        let (sample_rate, channels) = match (params.sample_rate, params.channels) {
+            (Some(sample_rate), Some(channels)) => (sample_rate, channels),
+            (sample_rate, channels) => {
+                while reader.spec.is_none() && !reader.finished {
+                    reader.decode_packet()?;
+                }
+                let spec = reader.spec.ok_or_else(|| {
+                    AppError::AudioFile("Unknown sample rate or channel layout".to_string())
+                })?;
+                (
+                    sample_rate.unwrap_or(spec.rate),
+                    channels.unwrap_or(spec.channels),
+                )
+            }
+        };
+
+        Ok(Self {
+            sample_rate,
+            channels: channels.count(),
+            reader: Reader::Decoded(Box::new(reader)),
+        })
+    }
+
+    /// Frames per second
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Number of interleaved channels
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Replace `buffer` with up to `frames` interleaved frames scaled to ±1.0
+    ///
+    /// Returns `false` once the file has no more samples.
+    pub fn read_block(&mut self, frames: usize, buffer: &mut Vec<f32>) -> AppResult<bool> {
+        buffer.clear();
+        let samples = frames * self.channels;
+        match &mut self.reader {
+            Reader::Wav(reader) => {
+                let spec = reader.spec();
+                match spec.sample_format {
+                    hound::SampleFormat::Float => {
+                        for sample in reader.samples::<f32>().take(samples) {
+                            buffer.push(sample?);
+                        }
+                    }
+                    hound::SampleFormat::Int => {
+                        let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+                        for sample in reader.samples::<i32>().take(samples) {
+                            buffer.push(sample? as f32 * scale);
+                        }
+                    }
+                }
+            }
+            Reader::Decoded(reader) => {
+                while reader.pending.len() < samples && !reader.finished {
+                    reader.decode_packet()?;
+                }
+                let take = samples.min(reader.pending.len());
+                buffer.extend(reader.pending.drain(..take));
+            }
+        }
+        // Drop a trailing partial frame from a truncated file
+        buffer.truncate(buffer.len() - buffer.len() % self.channels);
+        Ok(!buffer.is_empty())
+    }
+}
+
+impl SymphoniaReader {
+    /// Decode the next packet of the track into `pending`
+    fn decode_packet(&mut self) -> AppResult<()> {
+        let packet = match self.format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                self.finished = true;
+                return Ok(());
+            }
+            // A new stream begins; only the first one is read
+            Err(SymphoniaError::ResetRequired) => {
+                self.finished = true;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != self.track_id {
+            return Ok(());
+        }
+
+        match self.decoder.decode(&packet) {
+            Ok(decoded) => {
+                let mut samples =
+                    SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+                self.spec = Some(*decoded.spec());
+                samples.copy_interleaved_ref(decoded);
+                self.pending.extend_from_slice(samples.samples());
+                Ok(())
+            }
+            // Skip corrupt packets like a player would
+            Err(SymphoniaError::DecodeError(e)) => {
+                tracing::warn!(error = e, "Skipping undecodable packet");
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_test_wav(name: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("soundcheck-{}-{}.wav", name, std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for sample in [16384i16, -32768, 0, 8192, 1, 2] {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_reads_int_wav_scaled() {
+        let path = write_test_wav("hound");
+        let mut file = AudioFile::open(&path).unwrap();
+        assert_eq!((file.sample_rate(), file.channels()), (8000, 2));
+        let mut buffer = Vec::new();
+        assert!(file.read_block(2, &mut buffer).unwrap());
+        assert_eq!(buffer, vec![0.5, -1.0, 0.0, 0.25]);
+        assert!(file.read_block(2, &mut buffer).unwrap());
+        assert_eq!(buffer.len(), 2);
+        assert!(!file.read_block(2, &mut buffer).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_decodes_with_symphonia() {
+        let path = write_test_wav("symphonia");
+        let mut file = AudioFile::open_decoded(&path).unwrap();
+        assert_eq!((file.sample_rate(), file.channels()), (8000, 2));
+        let mut buffer = Vec::new();
+        assert!(file.read_block(2, &mut buffer).unwrap());
+        assert_eq!(buffer, vec![0.5, -1.0, 0.0, 0.25]);
+        assert!(file.read_block(8, &mut buffer).unwrap());
+        assert_eq!(buffer.len(), 2);
+        assert!(!file.read_block(2, &mut buffer).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
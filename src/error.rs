@@ -9,6 +9,12 @@ pub enum AppError {
     AudioDevice(String),
     /// Audio stream related errors
     AudioStream(String),
+    /// The input device disappeared while streaming
+    DeviceLost(String),
+    /// Audio file could not be read or decoded
+    AudioFile(String),
+    /// Test plan file is invalid
+    Plan(String),
 
     /// General I/O errors
     Io(std::io::Error),
@@ -19,6 +25,9 @@ impl fmt::Display for AppError {
         match self {
             AppError::AudioDevice(msg) => write!(f, "Audio device error: {}", msg),
             AppError::AudioStream(msg) => write!(f, "Audio stream error: {}", msg),
+            AppError::DeviceLost(msg) => write!(f, "Audio device lost: {}", msg),
+            AppError::AudioFile(msg) => write!(f, "Audio file error: {}", msg),
+            AppError::Plan(msg) => write!(f, "Test plan error: {}", msg),
             AppError::Io(err) => write!(f, "I/O error: {}", err),
         }
     }
@@ -68,5 +77,26 @@ impl From<cpal::PlayStreamError> for AppError {
     }
 }
 
+impl From<hound::Error> for AppError {
+    fn from(err: hound::Error) -> Self {
+        match err {
+            hound::Error::IoError(err) => AppError::Io(err),
+            err => AppError::AudioFile(err.to_string()),
+        }
+    }
+}
+
+impl From<symphonia::core::errors::Error> for AppError {
+    fn from(err: symphonia::core::errors::Error) -> Self {
+        match err {
+            symphonia::core::errors::Error::IoError(err) => AppError::Io(err),
+            symphonia::core::errors::Error::Unsupported(what) => {
+                AppError::AudioFile(format!("Unsupported format: {}", what))
+            }
+            err => AppError::AudioFile(err.to_string()),
+        }
+    }
+}
+
 /// Result type alias for application operations
 pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,344 @@
+//! Level events and marker export
+//!
+//! The audio thread detects threshold crossings, clipping and silence
+//! transitions sample by sample. Events are timestamped in seconds since
+//! monitoring started and can be written as Audacity labels, CSV or Reaper
+//! markers to line them up with a parallel recording.
+
+use crate::channels::ChannelInfo;
+use crate::error::AppResult;
+use crate::summary::csv_field;
+use clap::ValueEnum;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Kinds of level events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// The signal rose above the threshold
+    Threshold,
+    /// A sample reached full scale
+    Clip,
+    /// The signal stayed below the silence level for the hold time
+    SilenceStart,
+    /// Signal returned after silence
+    SilenceEnd,
+}
+
+impl EventKind {
+    /// Name used in exported files
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::Threshold => "threshold",
+            EventKind::Clip => "clip",
+            EventKind::SilenceStart => "silence-start",
+            EventKind::SilenceEnd => "silence-end",
+        }
+    }
+}
+
+/// One detected event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelEvent {
+    pub kind: EventKind,
+    /// Position of the channel in the selection
+    pub channel: usize,
+    /// Seconds since monitoring started
+    pub time_s: f64,
+    /// Sample level in dB when the event happened
+    pub level_db: f32,
+}
+
+/// Amplitude limits used for event detection
+#[derive(Debug, Clone, Copy)]
+pub struct EventLimits {
+    pub threshold: f32,
+    pub clip: f32,
+    pub silence: f32,
+    pub silence_hold_s: f64,
+}
+
+impl EventLimits {
+    /// Limits for the given linear threshold and the default clip and silence levels
+    pub fn new(linear_threshold: f32) -> Self {
+        use crate::constants::events::{CLIP_LEVEL, SILENCE_DB, SILENCE_HOLD_S};
+        Self {
+            threshold: linear_threshold,
+            clip: CLIP_LEVEL,
+            silence: crate::smoothing::db_to_amplitude(SILENCE_DB),
+            silence_hold_s: SILENCE_HOLD_S,
+        }
+    }
+}
+
+/// Per-channel event detection state, driven by the audio callback
+#[derive(Debug, Clone, Default)]
+pub struct EventDetector {
+    above_threshold: bool,
+    clipping: bool,
+    silent: bool,
+    quiet_since: Option<f64>,
+    block_above: bool,
+    block_clip: bool,
+    block_loud: bool,
+}
+
+impl EventDetector {
+    /// Examine one sample taken at `time_s`
+    pub fn sample(
+        &mut self,
+        amplitude: f32,
+        time_s: f64,
+        limits: &EventLimits,
+        mut emit: impl FnMut(EventKind, f64, f32),
+    ) {
+        if amplitude > limits.threshold {
+            self.block_above = true;
+            if !self.above_threshold {
+                self.above_threshold = true;
+                emit(EventKind::Threshold, time_s, amplitude);
+            }
+        }
+
+        if amplitude >= limits.clip {
+            self.block_clip = true;
+            if !self.clipping {
+                self.clipping = true;
+                emit(EventKind::Clip, time_s, amplitude);
+            }
+        }
+
+        if amplitude > limits.silence {
+            self.block_loud = true;
+            if self.silent {
+                self.silent = false;
+                emit(EventKind::SilenceEnd, time_s, amplitude);
+            }
+        }
+    }
+
+    /// Finish a buffer that started at `start_s` and ended at `end_s`
+    ///
+    /// Threshold and clip events re-arm after a buffer without them; silence
+    /// starts once whole buffers have stayed quiet for the hold time.
+    pub fn end_block(
+        &mut self,
+        start_s: f64,
+        end_s: f64,
+        limits: &EventLimits,
+        mut emit: impl FnMut(EventKind, f64, f32),
+    ) {
+        if !self.block_above {
+            self.above_threshold = false;
+        }
+        if !self.block_clip {
+            self.clipping = false;
+        }
+
+        if self.block_loud {
+            self.quiet_since = None;
+        } else {
+            let quiet_since = *self.quiet_since.get_or_insert(start_s);
+            if !self.silent && end_s - quiet_since >= limits.silence_hold_s {
+                self.silent = true;
+                emit(EventKind::SilenceStart, quiet_since, 0.0);
+            }
+        }
+
+        self.block_above = false;
+        self.block_clip = false;
+        self.block_loud = false;
+    }
+}
+
+/// File formats for `--events-out`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EventFormat {
+    /// Audacity label track (import with File > Import > Labels)
+    Audacity,
+    /// Comma-separated values with one event per row
+    Csv,
+    /// Reaper marker list (import in the Region/Marker Manager)
+    Reaper,
+}
+
+impl EventFormat {
+    /// Guess the format from the file extension, defaulting to CSV
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("txt") => EventFormat::Audacity,
+            _ => EventFormat::Csv,
+        }
+    }
+}
+
+/// Writes events to a file as they arrive
+pub struct EventWriter {
+    out: BufWriter<File>,
+    format: EventFormat,
+    /// Device, hardware channel index and label of each selected channel
+    channels: Vec<ChannelInfo>,
+    count: usize,
+}
+
+impl EventWriter {
+    /// Create the output file and write its header
+    pub fn create(path: &Path, format: EventFormat, channels: &[ChannelInfo]) -> AppResult<Self> {
+        let mut writer = Self {
+            out: BufWriter::new(File::create(path)?),
+            format,
+            channels: channels.to_vec(),
+            count: 0,
+        };
+        match format {
+            EventFormat::Audacity => {}
+            EventFormat::Csv => writeln!(writer.out, "time_s,event,channel,level_db,label,device")?,
+            EventFormat::Reaper => writeln!(writer.out, "#,Name,Start,End,Length")?,
+        }
+        Ok(writer)
+    }
+
+    /// Append one event
+    pub fn write(&mut self, event: &LevelEvent) -> AppResult<()> {
+        self.count += 1;
+        let info = &self.channels[event.channel];
+        let name = crate::channels::describe(info.channel, info.label.as_deref());
+        let name = match &info.device_spec {
+            Some(device) => format!("{} ch{}", device, name),
+            None => format!("ch{}", name),
+        };
+        let label = match event.kind {
+            EventKind::Threshold | EventKind::Clip => {
+                format!("{} {} {:.1} dB", event.kind.name(), name, event.level_db)
+            }
+            EventKind::SilenceStart | EventKind::SilenceEnd => {
+                format!("{} {}", event.kind.name(), name)
+            }
+        };
+
+        match self.format {
+            EventFormat::Audacity => writeln!(
+                self.out,
+                "{:.6}\t{:.6}\t{}",
+                event.time_s, event.time_s, label
+            )?,
+            EventFormat::Csv => writeln!(
+                self.out,
+                "{:.6},{},{},{:.1},{},{}",
+                event.time_s,
+                event.kind.name(),
+                info.channel,
+                event.level_db,
+                csv_field(info.label.as_deref().unwrap_or_default()),
+                csv_field(&info.device)
+            )?,
+            EventFormat::Reaper => writeln!(
+                self.out,
+                "M{},{},{},,",
+                self.count,
+                label,
+                format_reaper_time(event.time_s)
+            )?,
+        }
+        Ok(())
+    }
+
+    /// Flush buffered events to disk
+    pub fn flush(&mut self) -> AppResult<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Format seconds as Reaper's `m:ss.mmm` time
+fn format_reaper_time(time_s: f64) -> String {
+    let millis = (time_s * 1000.0).round() as u64;
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        (millis / 1000) % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(
+        detector: &mut EventDetector,
+        blocks: &[&[f32]],
+        limits: &EventLimits,
+    ) -> Vec<(EventKind, f64)> {
+        let mut events = Vec::new();
+        let mut time = 0.0;
+        for block in blocks {
+            let start = time;
+            for &sample in *block {
+                detector.sample(sample, time, limits, |k, t, _| events.push((k, t)));
+                time += 1.0;
+            }
+            detector.end_block(start, time, limits, |k, t, _| events.push((k, t)));
+        }
+        events
+    }
+
+    #[test]
+    fn test_threshold_and_clip_events_are_sample_accurate() {
+        let limits = EventLimits {
+            threshold: 0.5,
+            clip: 1.0,
+            silence: 0.01,
+            silence_hold_s: 100.0,
+        };
+        let mut detector = EventDetector::default();
+        let events = run(
+            &mut detector,
+            &[&[0.1, 0.6, 1.0, 0.7], &[0.1, 0.1], &[0.1, 0.8]],
+            &limits,
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                (EventKind::Threshold, 1.0),
+                (EventKind::Clip, 2.0),
+                (EventKind::Threshold, 7.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_silence_transitions() {
+        let limits = EventLimits {
+            threshold: 0.5,
+            clip: 1.0,
+            silence: 0.01,
+            silence_hold_s: 4.0,
+        };
+        let mut detector = EventDetector::default();
+        let events = run(
+            &mut detector,
+            &[
+                &[0.1, 0.1],
+                &[0.0, 0.0],
+                &[0.0, 0.0],
+                &[0.0, 0.0],
+                &[0.0, 0.2],
+            ],
+            &limits,
+        );
+
+        assert_eq!(
+            events,
+            vec![(EventKind::SilenceStart, 2.0), (EventKind::SilenceEnd, 9.0)]
+        );
+    }
+
+    #[test]
+    fn test_reaper_time_format() {
+        assert_eq!(format_reaper_time(75.5), "1:15.500");
+        assert_eq!(format_reaper_time(0.0015), "0:00.002");
+    }
+}
@@ -0,0 +1,63 @@
+//! Minimal HTTP/1.1 handling for the built-in network services
+
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Log a failed `accept` and pause before the next one
+///
+/// Errors such as running out of file descriptors persist, so retrying at
+/// once would spin.
+pub async fn accept_failed(service: &str, error: std::io::Error) {
+    tracing::warn!(service, %error, "Failed to accept connection");
+    tokio::time::sleep(std::time::Duration::from_millis(
+        crate::constants::audio::RECONNECT_INTERVAL_MS,
+    ))
+    .await;
+}
+
+/// Read a request line and headers, returning the method and path
+///
+/// Returns `None` if the connection closed or the request was malformed.
+pub async fn read_request(stream: &mut TcpStream) -> Option<(String, String)> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await.ok()?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    // Skip headers up to the blank line
+    loop {
+        let mut header = String::new();
+        let read = reader.read_line(&mut header).await.ok()?;
+        if read == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    Some((method, path))
+}
+
+/// Write a complete response and close the exchange
+pub async fn write_response<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await
+}
+
+/// Respond with 404 Not Found
+pub async fn not_found<W: AsyncWrite + Unpin>(stream: &mut W) -> std::io::Result<()> {
+    write_response(stream, "404 Not Found", "text/plain", b"Not found\n").await
+}
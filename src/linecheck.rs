@@ -0,0 +1,314 @@
+//! Guided line check for the `linecheck` command
+//!
+//! Walks through the selected channels one at a time, asks the operator to
+//! make a sound on each and records whether the signal arrived there, nowhere,
+//! or on a different channel because of a crossed cable.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// What was found for one expected channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Signal arrived on the expected channel at this level in dB
+    Present { level_db: f32 },
+    /// No signal before the operator skipped or the timeout passed
+    Missing,
+    /// Signal arrived on the channel at this position in the selection instead
+    Swapped { found: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Phase {
+    /// Ignoring input until the given time; `None` before the first update
+    Settling {
+        until: Option<Instant>,
+    },
+    /// Listening for the current channel since `since`
+    Listening {
+        since: Instant,
+        /// Another channel with signal, and since when
+        other: Option<(usize, Instant)>,
+    },
+    Finished,
+}
+
+/// Progress through a line check
+pub struct LineCheck {
+    channels: Vec<usize>,
+    labels: Vec<String>,
+    threshold_db: f32,
+    timeout: Option<Duration>,
+    current: usize,
+    phase: Phase,
+    outcomes: Vec<Outcome>,
+}
+
+impl LineCheck {
+    /// Check `channels` in order; `labels` may be empty or name every channel
+    pub fn new(
+        channels: Vec<usize>,
+        labels: Vec<String>,
+        threshold_db: i32,
+        timeout: Option<Duration>,
+    ) -> Self {
+        Self {
+            outcomes: Vec::with_capacity(channels.len()),
+            channels,
+            labels,
+            threshold_db: threshold_db as f32,
+            timeout,
+            current: 0,
+            phase: Phase::Settling { until: None },
+        }
+    }
+
+    /// Feed the current level of every selected channel
+    pub fn update(&mut self, levels_db: &[f32], now: Instant) {
+        match self.phase {
+            Phase::Settling { until: None } => {
+                self.phase = Phase::Settling {
+                    until: Some(now + settle_time()),
+                };
+            }
+            Phase::Settling { until: Some(until) } => {
+                if now >= until {
+                    self.phase = Phase::Listening {
+                        since: now,
+                        other: None,
+                    };
+                }
+            }
+            Phase::Listening { since, other } => {
+                let above = |i: usize| levels_db[i] >= self.threshold_db;
+                if above(self.current) {
+                    self.record(
+                        Outcome::Present {
+                            level_db: levels_db[self.current],
+                        },
+                        now,
+                    );
+                    return;
+                }
+
+                // The loudest other channel with signal, held long enough
+                let loudest = (0..levels_db.len())
+                    .filter(|&i| i != self.current && above(i))
+                    .max_by(|&a, &b| levels_db[a].total_cmp(&levels_db[b]));
+                let other = match (loudest, other) {
+                    (Some(i), Some((j, start))) if i == j => Some((i, start)),
+                    (Some(i), _) => Some((i, now)),
+                    (None, _) => None,
+                };
+                let hold = Duration::from_secs_f32(crate::constants::linecheck::SWAP_HOLD_S);
+                if let Some((found, start)) = other
+                    && now.duration_since(start) >= hold
+                {
+                    self.record(Outcome::Swapped { found }, now);
+                } else if self
+                    .timeout
+                    .is_some_and(|timeout| now.duration_since(since) >= timeout)
+                {
+                    self.record(Outcome::Missing, now);
+                } else {
+                    self.phase = Phase::Listening { since, other };
+                }
+            }
+            Phase::Finished => {}
+        }
+    }
+
+    /// Give up on the current channel and mark it missing
+    pub fn skip(&mut self, now: Instant) {
+        if !self.finished() {
+            self.record(Outcome::Missing, now);
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome, now: Instant) {
+        tracing::info!(
+            channel = self.channels[self.current],
+            ?outcome,
+            "Line check result"
+        );
+        self.outcomes.push(outcome);
+        self.current += 1;
+        self.phase = if self.current == self.channels.len() {
+            Phase::Finished
+        } else {
+            Phase::Settling {
+                until: Some(now + settle_time()),
+            }
+        };
+    }
+
+    pub fn finished(&self) -> bool {
+        matches!(self.phase, Phase::Finished)
+    }
+
+    /// Instruction for the operator
+    pub fn status(&self) -> String {
+        let step = format!("{} of {}", self.current + 1, self.channels.len());
+        match self.phase {
+            Phase::Finished => "Line check complete.".to_string(),
+            Phase::Settling { .. } => match self.outcomes.last() {
+                Some(outcome) => format!(
+                    "{} Get ready for {}... ({})",
+                    self.describe(self.current - 1, outcome),
+                    self.name(self.current),
+                    step
+                ),
+                None => format!("Get ready for {}... ({})", self.name(self.current), step),
+            },
+            Phase::Listening { .. } => format!(
+                "Talk into {} ({}). Press s to skip, Escape to quit.",
+                self.name(self.current),
+                step
+            ),
+        }
+    }
+
+    /// Channel and label at position `i`, e.g. `channel 5: Snare`
+    fn name(&self, i: usize) -> String {
+        match self.labels.get(i) {
+            Some(label) => format!("channel {}: {}", self.channels[i], label),
+            None => format!("channel {}", self.channels[i]),
+        }
+    }
+
+    fn describe(&self, i: usize, outcome: &Outcome) -> String {
+        match outcome {
+            Outcome::Present { level_db } => {
+                format!(
+                    "{} present at {:.1} dB.",
+                    capitalize(&self.name(i)),
+                    level_db
+                )
+            }
+            Outcome::Missing => format!("{} missing.", capitalize(&self.name(i))),
+            Outcome::Swapped { found } => format!(
+                "{} swapped: signal arrived on {}.",
+                capitalize(&self.name(i)),
+                self.name(*found)
+            ),
+        }
+    }
+
+    /// Results so far, for printing once the check ends
+    pub fn summary(&self) -> Summary<'_> {
+        Summary(self)
+    }
+
+    /// Whether every channel checked so far was present
+    pub fn passed(&self) -> bool {
+        self.outcomes
+            .iter()
+            .all(|outcome| matches!(outcome, Outcome::Present { .. }))
+    }
+}
+
+fn settle_time() -> Duration {
+    Duration::from_secs_f32(crate::constants::linecheck::SETTLE_S)
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Per-channel results followed by the counts
+pub struct Summary<'a>(&'a LineCheck);
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let check = self.0;
+        for (i, outcome) in check.outcomes.iter().enumerate() {
+            writeln!(f, "{}", check.describe(i, outcome))?;
+        }
+        let count =
+            |wanted: fn(&Outcome) -> bool| check.outcomes.iter().filter(|o| wanted(o)).count();
+        write!(
+            f,
+            "{} present, {} missing, {} swapped",
+            count(|o| matches!(o, Outcome::Present { .. })),
+            count(|o| matches!(o, Outcome::Missing)),
+            count(|o| matches!(o, Outcome::Swapped { .. })),
+        )?;
+        let unchecked = check.channels.len() - check.outcomes.len();
+        if unchecked > 0 {
+            write!(f, ", {} not checked", unchecked)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the check from `start` with `levels` held for `seconds`
+    fn hold(check: &mut LineCheck, start: &mut Instant, levels: &[f32], seconds: f32) {
+        let end = *start + Duration::from_secs_f32(seconds);
+        while *start < end && !check.finished() {
+            check.update(levels, *start);
+            *start += Duration::from_millis(50);
+        }
+    }
+
+    #[test]
+    fn test_finds_present_missing_and_swapped() {
+        let labels = vec!["Kick".to_string(), "Snare".to_string(), "Hat".to_string()];
+        let mut check = LineCheck::new(vec![4, 5, 6], labels, -30, None);
+        let mut now = Instant::now();
+        let quiet = [-60.0, -60.0, -60.0];
+
+        hold(&mut check, &mut now, &quiet, 1.5);
+        assert!(check.status().starts_with("Talk into channel 4: Kick"));
+        hold(&mut check, &mut now, &[-12.0, -60.0, -60.0], 0.1);
+
+        // Snare's signal shows up on the hat channel
+        hold(&mut check, &mut now, &quiet, 1.5);
+        hold(&mut check, &mut now, &[-60.0, -60.0, -20.0], 1.0);
+        assert!(
+            check
+                .status()
+                .contains("swapped: signal arrived on channel 6: Hat")
+        );
+
+        hold(&mut check, &mut now, &quiet, 1.5);
+        check.skip(now);
+        assert!(check.finished());
+        assert!(!check.passed());
+        assert_eq!(
+            check.outcomes,
+            vec![
+                Outcome::Present { level_db: -12.0 },
+                Outcome::Swapped { found: 2 },
+                Outcome::Missing,
+            ]
+        );
+        assert!(
+            check
+                .summary()
+                .to_string()
+                .ends_with("1 present, 1 missing, 1 swapped")
+        );
+    }
+
+    #[test]
+    fn test_brief_signal_elsewhere_is_not_a_swap() {
+        let mut check = LineCheck::new(vec![0, 1], Vec::new(), -30, Some(Duration::from_secs(2)));
+        let mut now = Instant::now();
+        hold(&mut check, &mut now, &[-60.0, -60.0], 1.5);
+        hold(&mut check, &mut now, &[-60.0, -20.0], 0.1);
+        hold(&mut check, &mut now, &[-60.0, -60.0], 0.5);
+        assert!(check.outcomes.is_empty());
+
+        // Nothing on channel 0 before the timeout
+        hold(&mut check, &mut now, &[-60.0, -60.0], 2.0);
+        assert_eq!(check.outcomes, vec![Outcome::Missing]);
+    }
+}
@@ -0,0 +1,29 @@
+//! Diagnostic logging to a file
+//!
+//! Logs go to the file given with `--log-file` so they never draw over the
+//! terminal UI. Verbosity follows `RUST_LOG` and defaults to `info`.
+
+use crate::error::AppResult;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+
+/// Install the global logger if a log file was requested
+pub fn init(log_file: Option<&Path>) -> AppResult<()> {
+    let Some(path) = log_file else {
+        return Ok(());
+    };
+
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .init();
+
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "soundcheck started");
+    Ok(())
+}
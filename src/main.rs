@@ -1,11 +1,33 @@
+mod analyze;
 mod app;
 mod audio;
+mod channels;
+mod checks;
+mod clock;
 mod config;
 mod constants;
+mod control;
+mod decode;
 mod error;
+mod events;
+mod http;
+mod linecheck;
+mod logging;
+mod metrics;
+mod mqtt;
+mod osc;
+mod plan;
+mod remote;
+mod scan;
+mod simulate;
 mod smoothing;
 mod state;
+mod stats;
+mod summary;
+mod terminal;
+mod trigger;
 mod ui;
+mod web;
 
 use clap::Parser;
 use cpal::traits::{DeviceTrait, HostTrait};
@@ -41,6 +63,11 @@ async fn main() {
 
     let args = Args::parse();
 
+    if let Err(e) = logging::init(args.log_file.as_deref()) {
+        eprintln!("Could not open log file: {}", e);
+        std::process::exit(ExitCode::Error as i32);
+    }
+
     match args.command {
         Commands::Detect(detect_args) => {
             // Create config from detect args
@@ -62,7 +89,7 @@ async fn main() {
                         }
                         Err(e) => {
                             eprintln!("Application error: {}", e);
-                            std::process::exit(ExitCode::Error as i32);
+                            std::process::exit(run_result.exit_code as i32);
                         }
                     }
                 }
@@ -88,6 +115,8 @@ async fn main() {
                 }
             };
 
+            let names = config.channel_names();
+
             // Create app
             let mut app = match app::App::new_with_config(config) {
                 Ok(a) => a,
@@ -98,7 +127,12 @@ async fn main() {
             };
 
             // Run max monitoring
-            match app.run_max(max_args.seconds).await {
+            let result = app.run_max(max_args.seconds).await;
+
+            // Leave the alternate screen before printing results or errors
+            drop(app);
+
+            match result {
                 Ok(max_levels) => {
                     if max_args.quiet {
                         for &level in &max_levels {
@@ -106,14 +140,14 @@ async fn main() {
                         }
                     } else {
                         println!("Maximum dB levels detected:");
-                        for (i, &level) in max_levels.iter().enumerate() {
-                            println!("Channel {}: {:.1} dB", i, level);
+                        for (name, &level) in names.iter().zip(&max_levels) {
+                            println!("Channel {}: {:.1} dB", name, level);
                         }
                     }
                 }
                 Err(e) => {
                     eprintln!("Error during monitoring: {}", e);
-                    std::process::exit(app::ExitCode::Error as i32);
+                    std::process::exit(ExitCode::from(&e) as i32);
                 }
             }
         }
@@ -127,6 +161,8 @@ async fn main() {
                 }
             };
 
+            let names = config.channel_names();
+
             // Create app
             let mut app = match app::App::new_with_config(config) {
                 Ok(a) => a,
@@ -137,7 +173,14 @@ async fn main() {
             };
 
             // Run average monitoring
-            match app.run_average(average_args.seconds).await {
+            let result = app
+                .run_average(average_args.seconds, average_args.method)
+                .await;
+
+            // Leave the alternate screen before printing results or errors
+            drop(app);
+
+            match result {
                 Ok(avg_levels) => {
                     if average_args.quiet {
                         for &level in &avg_levels {
@@ -145,15 +188,296 @@ async fn main() {
                         }
                     } else {
                         println!("Average dB levels detected:");
-                        for (i, &level) in avg_levels.iter().enumerate() {
-                            println!("Channel {}: {:.1} dB", i, level);
+                        for (name, &level) in names.iter().zip(&avg_levels) {
+                            println!("Channel {}: {:.1} dB", name, level);
                         }
                     }
                 }
                 Err(e) => {
                     eprintln!("Error during monitoring: {}", e);
+                    std::process::exit(ExitCode::from(&e) as i32);
+                }
+            }
+        }
+        Commands::Stats(stats_args) => {
+            // Create config from stats args
+            let config = match config::Config::from_stats_args(&stats_args) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Configuration error: {}", e);
                     std::process::exit(app::ExitCode::Error as i32);
                 }
+            };
+
+            let names = config.channel_names();
+
+            // Create app
+            let mut app = match app::App::new_with_config(config) {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("Setup error: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            };
+
+            // Run stats monitoring
+            let result = app.run_stats(stats_args.seconds, &stats_args.stats).await;
+
+            // Leave the alternate screen before printing results or errors
+            drop(app);
+
+            match result {
+                Ok(results) => {
+                    let num_channels = results.first().map_or(0, |r| r.len());
+                    if stats_args.quiet {
+                        for ch in 0..num_channels {
+                            let values: Vec<String> = results
+                                .iter()
+                                .map(|r| (r[ch].round() as i32).to_string())
+                                .collect();
+                            println!("{}", values.join(" "));
+                        }
+                    } else {
+                        println!("dB statistics detected:");
+                        for ch in 0..num_channels {
+                            let values: Vec<String> = stats_args
+                                .stats
+                                .iter()
+                                .zip(&results)
+                                .map(|(kind, r)| format!("{} {:.1}", kind.name(), r[ch]))
+                                .collect();
+                            println!("Channel {}: {}", names[ch], values.join(", "));
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error during monitoring: {}", e);
+                    std::process::exit(ExitCode::from(&e) as i32);
+                }
+            }
+        }
+        Commands::Assert(assert_args) => {
+            let config = match config::Config::from_assert_args(&assert_args) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Configuration error: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            };
+
+            // Capture headless so the report is the only output
+            let channels = config.channel_info(&[]);
+            let mut app = app::App::new_headless(config);
+            let statistics = checks::Measurements::statistics(channels.len());
+            let measurements = match app
+                .run_statistics(Some(assert_args.seconds), statistics)
+                .await
+            {
+                Ok(results) => checks::Measurements::from_results(results),
+                Err(e) => {
+                    eprintln!("Error during monitoring: {}", e);
+                    std::process::exit(ExitCode::from(&e) as i32);
+                }
+            };
+
+            let results = checks::evaluate(&assert_args.checks, &channels, &measurements);
+            let report = checks::Report(&results);
+            println!("{}", report);
+            if let Some(path) = &assert_args.junit
+                && let Err(e) = checks::write_junit(path, &results, assert_args.seconds)
+            {
+                eprintln!("Failed to write JUnit report: {}", e);
+                std::process::exit(app::ExitCode::Error as i32);
+            }
+            if !report.passed() {
+                std::process::exit(app::ExitCode::CheckFailed as i32);
+            }
+        }
+        Commands::Plan(plan_args) => {
+            let test_plan = match plan::Plan::load(&plan_args.file) {
+                Ok(p) => p,
+                Err(e) => {
+                    eprintln!("Configuration error: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            };
+
+            let report = match plan::run(&test_plan, plan_args.device, plan_args.simulate).await {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Error during plan: {}", e);
+                    std::process::exit(ExitCode::from(&e) as i32);
+                }
+            };
+
+            let failed = report
+                .steps
+                .iter()
+                .filter(|s| s.passed == Some(false))
+                .count();
+            println!(
+                "{} of {} steps run, {} failed, {:.1} s",
+                report.steps.len(),
+                test_plan.steps.len(),
+                failed,
+                report.duration_s
+            );
+            if let Some(path) = &plan_args.report
+                && let Err(e) = report.write(path)
+            {
+                eprintln!("Failed to write report: {}", e);
+                std::process::exit(app::ExitCode::Error as i32);
+            }
+            std::process::exit(report.exit_code() as i32);
+        }
+        Commands::Linecheck(linecheck_args) => {
+            let config = match config::Config::from_linecheck_args(&linecheck_args) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Configuration error: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            };
+
+            let mut line_check = linecheck::LineCheck::new(
+                config.channels.clone(),
+                config.labels.clone(),
+                config.threshold_db,
+                linecheck_args
+                    .timeout
+                    .map(std::time::Duration::from_secs_f32),
+            );
+            let mut app = match app::App::new_with_config(config) {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("Setup error: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            };
+            let run_result = app.run_line_check(&mut line_check).await;
+
+            // Leave the alternate screen before printing the summary
+            drop(app);
+
+            if let Err(e) = run_result.result {
+                eprintln!("Application error: {}", e);
+                std::process::exit(run_result.exit_code as i32);
+            }
+            println!("{}", line_check.summary());
+            match run_result.exit_code {
+                ExitCode::Success if !line_check.passed() => {
+                    std::process::exit(ExitCode::CheckFailed as i32);
+                }
+                code => std::process::exit(code as i32),
+            }
+        }
+        Commands::Scan(scan_args) => {
+            if let Err(e) = scan_args.validate() {
+                eprintln!("Configuration error: {}", e);
+                std::process::exit(app::ExitCode::Error as i32);
+            }
+            if let Err(e) = scan::run(&scan_args).await {
+                eprintln!("Application error: {}", e);
+                std::process::exit(app::ExitCode::from(&e) as i32);
+            }
+        }
+        Commands::Serve(serve_args) => {
+            // Create config from serve args
+            let config = match config::Config::from_serve_args(serve_args) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Configuration error: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            };
+
+            // Run headless until interrupted
+            let mut app = app::App::new_headless(config);
+            let run_result = app
+                .monitor(app::Mode::Capture { seconds: None }, &mut [])
+                .await;
+            if let Err(e) = run_result.result {
+                eprintln!("Application error: {}", e);
+            }
+            std::process::exit(run_result.exit_code as i32);
+        }
+        Commands::Web(web_args) => {
+            // Create config from web args
+            let config = match config::Config::from_web_args(web_args) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Configuration error: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            };
+
+            // Run headless until interrupted
+            let mut app = app::App::new_headless(config);
+            let run_result = app
+                .monitor(app::Mode::Capture { seconds: None }, &mut [])
+                .await;
+            if let Err(e) = run_result.result {
+                eprintln!("Application error: {}", e);
+            }
+            std::process::exit(run_result.exit_code as i32);
+        }
+        Commands::Analyze(analyze_args) => {
+            if let Some(spec) = analyze_args
+                .channels
+                .iter()
+                .flatten()
+                .find(|s| s.device.is_some())
+            {
+                eprintln!(
+                    "Configuration error: Files have no devices to take channels from: {}",
+                    spec
+                );
+                std::process::exit(app::ExitCode::Error as i32);
+            }
+            let analyze::FoundFiles { files, unreadable } =
+                match analyze::collect_files(&analyze_args.files, analyze_args.recursive) {
+                    Ok(found) => found,
+                    Err(e) => {
+                        eprintln!("Configuration error: {}", e);
+                        std::process::exit(app::ExitCode::Error as i32);
+                    }
+                };
+
+            // Decode in parallel, reporting in the order the files were found
+            let mut entries = Vec::with_capacity(files.len());
+            analyze::analyze_files(&files, analyze_args.channels.as_deref(), |path, result| {
+                match &result {
+                    Ok(report) => print!("{}", report),
+                    Err(e) => eprintln!("{}: {}", path.display(), e),
+                }
+                entries.push(summary::Entry::new(path, result));
+            });
+            for (path, e) in unreadable {
+                eprintln!("{}: {}", path.display(), e);
+                entries.push(summary::Entry::new(&path, Err(e)));
+            }
+
+            let aggregate = summary::Aggregate::new(&entries);
+            if entries.len() > 1 {
+                println!("{}", aggregate);
+            }
+            if let Some(path) = &analyze_args.summary {
+                let format = analyze_args
+                    .summary_format
+                    .unwrap_or_else(|| summary::SummaryFormat::from_path(path));
+                if let Err(e) = summary::write_summary(path, format, &entries, &aggregate) {
+                    eprintln!("Failed to write summary: {}", e);
+                    std::process::exit(app::ExitCode::Error as i32);
+                }
+            }
+            if aggregate.errors > 0 {
+                std::process::exit(app::ExitCode::Error as i32);
+            }
+        }
+        Commands::Watch(watch_args) => {
+            if let Err(e) = remote::watch(&watch_args.addr).await {
+                eprintln!("Application error: {}", e);
+                std::process::exit(app::ExitCode::from(&e) as i32);
             }
         }
     }
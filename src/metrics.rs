@@ -0,0 +1,185 @@
+//! Prometheus metrics endpoint
+
+use crate::channels::ChannelInfo;
+use crate::http;
+use crate::state::{ChannelLevels, SharedState};
+use std::fmt::Write;
+use std::sync::atomic::Ordering;
+use tokio::net::TcpListener;
+
+/// Serve `/metrics` until the task is dropped
+pub async fn serve(listener: TcpListener, shared_state: SharedState, channels: Vec<ChannelInfo>) {
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                crate::http::accept_failed("metrics", e).await;
+                continue;
+            }
+        };
+        let body = render(&shared_state, &channels);
+        tokio::spawn(async move {
+            let result = match http::read_request(&mut stream).await {
+                Some((method, path)) if method == "GET" && path == "/metrics" => {
+                    http::write_response(
+                        &mut stream,
+                        "200 OK",
+                        "text/plain; version=0.0.4",
+                        body.as_bytes(),
+                    )
+                    .await
+                }
+                Some(_) => http::not_found(&mut stream).await,
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                tracing::debug!(%peer, error = %e, "Metrics request failed");
+            }
+        });
+    }
+}
+
+/// Name, type, help text and value of each per-channel metric
+type ChannelMetric = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&ChannelLevels) -> f64,
+);
+
+const CHANNEL_METRICS: [ChannelMetric; 5] = [
+    (
+        "soundcheck_level_db",
+        "gauge",
+        "Peak level of the most recent buffer in dBFS",
+        |l| l.current_db.load() as f64,
+    ),
+    (
+        "soundcheck_peak_db",
+        "gauge",
+        "Highest buffer peak since start or last reset in dBFS",
+        |l| l.peak_db.load() as f64,
+    ),
+    (
+        "soundcheck_rms_db",
+        "gauge",
+        "RMS level of the most recent buffer in dBFS",
+        |l| l.rms_db.load() as f64,
+    ),
+    (
+        "soundcheck_triggers_total",
+        "counter",
+        "Number of threshold crossings",
+        |l| l.trigger_count.load(Ordering::Relaxed) as f64,
+    ),
+    (
+        "soundcheck_clips_total",
+        "counter",
+        "Number of clip events",
+        |l| l.clip_count.load(Ordering::Relaxed) as f64,
+    ),
+];
+
+/// Render the current levels and counters in Prometheus exposition format
+///
+/// Channels are told apart by the device they come from as well as their
+/// index, so the same index on two devices gives two series.
+pub fn render(shared_state: &SharedState, channels: &[ChannelInfo]) -> String {
+    let mut out = String::new();
+
+    for (name, kind, help, value) in CHANNEL_METRICS {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (levels, info) in shared_state.channels().iter().zip(channels) {
+            let label = match &info.label {
+                Some(label) => format!(",label=\"{}\"", escape_label(label)),
+                None => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "{}{{device=\"{}\",channel=\"{}\"{}}} {}",
+                name,
+                escape_label(&info.device),
+                info.channel,
+                label,
+                value(levels)
+            );
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP soundcheck_stream_errors_total Number of errors reported by the audio stream"
+    );
+    let _ = writeln!(out, "# TYPE soundcheck_stream_errors_total counter");
+    let _ = writeln!(
+        out,
+        "soundcheck_stream_errors_total {}",
+        shared_state.stream_errors().load(Ordering::Relaxed)
+    );
+
+    out
+}
+
+/// Escape a label value for the exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(device: &str, channel: usize, label: Option<&str>) -> ChannelInfo {
+        ChannelInfo {
+            channel,
+            device_spec: None,
+            device: device.to_string(),
+            label: label.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_render_includes_every_channel() {
+        let shared_state = SharedState::new(2, 1.0);
+        shared_state.channels()[1].current_db.store(-12.5);
+        shared_state.channels()[1]
+            .clip_count
+            .store(3, Ordering::Relaxed);
+
+        let out = render(
+            &shared_state,
+            &[info("USB \"Mic\"", 0, None), info("USB \"Mic\"", 3, None)],
+        );
+        assert!(
+            out.contains("soundcheck_level_db{device=\"USB \\\"Mic\\\"\",channel=\"3\"} -12.5")
+        );
+        assert!(out.contains("soundcheck_clips_total{device=\"USB \\\"Mic\\\"\",channel=\"3\"} 3"));
+        assert!(out.contains("# TYPE soundcheck_stream_errors_total counter"));
+
+        let out = render(
+            &shared_state,
+            &[info("Mic", 0, Some("Kick")), info("Mic", 3, Some("Snare"))],
+        );
+        assert!(
+            out.contains("soundcheck_level_db{device=\"Mic\",channel=\"3\",label=\"Snare\"} -12.5")
+        );
+    }
+
+    #[test]
+    fn test_render_keeps_devices_apart() {
+        let shared_state = SharedState::new(2, 1.0);
+        shared_state.channels()[0].current_db.store(-6.0);
+        shared_state.channels()[1].current_db.store(-30.0);
+
+        let out = render(
+            &shared_state,
+            &[info("USB", 0, None), info("Built-in", 0, None)],
+        );
+        assert!(out.contains("soundcheck_level_db{device=\"USB\",channel=\"0\"} -6"));
+        assert!(out.contains("soundcheck_level_db{device=\"Built-in\",channel=\"0\"} -30"));
+    }
+}
@@ -0,0 +1,253 @@
+//! MQTT publishing of levels and activity state
+//!
+//! Publishes under `<topic>/`:
+//! - `levels`: JSON summary of every channel, every `--mqtt-interval` seconds
+//! - `channel/<n>/trigger`: retained `ON` while the channel is above the
+//!   threshold, `OFF` once it has stayed below it for a moment
+//! - `channel/<n>/silence`: retained `ON` while the channel is silent
+//! - `status`: retained `online`, or `offline` when monitoring stops
+//!
+//! Channels taken from another device with `--channels <device>:<n>` use
+//! `channel/<device>/<n>/...`.
+
+use crate::channels::ChannelInfo;
+use crate::error::{AppError, AppResult};
+use crate::events::{EventKind, LevelEvent};
+use crate::state::{AppState, SharedState};
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Outgoing, QoS};
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Publishes levels and activity state to an MQTT broker
+pub struct MqttPublisher {
+    client: AsyncClient,
+    event_loop: JoinHandle<()>,
+    topic: String,
+    interval: Duration,
+    last_summary: Option<Instant>,
+    /// Device, hardware channel index and label of each selected channel
+    channels: Vec<ChannelInfo>,
+    /// Highest level of each channel since the last summary
+    max_db: Vec<f32>,
+    /// Published trigger state of each channel
+    active: Vec<bool>,
+    /// When each active channel last went below the threshold
+    below_since: Vec<Option<Instant>>,
+}
+
+impl MqttPublisher {
+    /// Connect to `broker` (host or host:port) in the background
+    pub fn new(
+        broker: &str,
+        topic: &str,
+        interval_s: f32,
+        channels: &[ChannelInfo],
+    ) -> AppResult<Self> {
+        let (host, port) = parse_broker(broker)?;
+        let topic = topic.trim_end_matches('/').to_string();
+        let status_topic = format!("{}/status", topic);
+
+        let mut options =
+            MqttOptions::new(format!("soundcheck-{}", std::process::id()), host, port);
+        options.set_keep_alive(Duration::from_secs(crate::constants::mqtt::KEEP_ALIVE_S));
+        options.set_last_will(LastWill::new(
+            &status_topic,
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        let (client, mut event_loop) =
+            AsyncClient::new(options, crate::constants::mqtt::QUEUE_CAPACITY);
+        tracing::info!(broker, %topic, "Publishing to MQTT");
+
+        // Drive the connection, reconnecting after errors until disconnected
+        let event_loop = tokio::spawn(async move {
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!(error = %e, "MQTT connection error");
+                        tokio::time::sleep(Duration::from_millis(
+                            crate::constants::audio::RECONNECT_INTERVAL_MS,
+                        ))
+                        .await;
+                    }
+                }
+            }
+        });
+
+        let publisher = Self {
+            client,
+            event_loop,
+            topic,
+            interval: Duration::from_secs_f32(interval_s),
+            last_summary: None,
+            channels: channels.to_vec(),
+            max_db: vec![crate::constants::audio::MIN_DB_LEVEL as f32; channels.len()],
+            active: vec![false; channels.len()],
+            below_since: vec![None; channels.len()],
+        };
+        publisher.publish(&status_topic, "online", true);
+        // Replace state retained by an earlier run
+        for i in 0..channels.len() {
+            publisher.publish_channel(i, "trigger", "OFF");
+            publisher.publish_channel(i, "silence", "OFF");
+        }
+        Ok(publisher)
+    }
+
+    /// Publish state changes from one snapshot and a summary when due
+    ///
+    /// Channels read as inactive while the trigger is disarmed.
+    pub fn update(
+        &mut self,
+        state: &AppState,
+        armed: bool,
+        shared_state: &SharedState,
+        events: &[LevelEvent],
+        now: Instant,
+    ) {
+        let hold = Duration::from_secs_f64(crate::constants::mqtt::TRIGGER_HOLD_S);
+        for i in 0..self.channels.len() {
+            self.max_db[i] = self.max_db[i].max(state.current_db[i]);
+
+            let active = if armed && state.threshold_reached[i] {
+                self.below_since[i] = None;
+                true
+            } else if !armed || state.current_db[i] < state.threshold_db as f32 {
+                let below_since = *self.below_since[i].get_or_insert(now);
+                self.active[i] && now.duration_since(below_since) < hold
+            } else {
+                self.active[i]
+            };
+            if active != self.active[i] {
+                self.active[i] = active;
+                self.publish_channel(i, "trigger", if active { "ON" } else { "OFF" });
+            }
+        }
+
+        for event in events {
+            match event.kind {
+                EventKind::SilenceStart => self.publish_channel(event.channel, "silence", "ON"),
+                EventKind::SilenceEnd => self.publish_channel(event.channel, "silence", "OFF"),
+                EventKind::Threshold | EventKind::Clip => {}
+            }
+        }
+
+        if self
+            .last_summary
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return;
+        }
+        self.last_summary = Some(now);
+
+        let channels: Vec<_> = self
+            .channels
+            .iter()
+            .zip(shared_state.channels())
+            .enumerate()
+            .map(|(i, (info, levels))| {
+                let mut entry = json!({
+                    "device": info.device,
+                    "channel": info.channel,
+                    "level_db": state.current_db[i],
+                    "max_db": self.max_db[i],
+                    "rms_db": levels.rms_db.load(),
+                    "active": self.active[i],
+                });
+                if let Some(label) = &info.label {
+                    entry["label"] = label.as_str().into();
+                }
+                entry
+            })
+            .collect();
+        let summary = json!({
+            "device": state.device_name,
+            "threshold_db": state.threshold_db,
+            "channels": channels,
+        });
+        self.publish(
+            &format!("{}/levels", self.topic),
+            summary.to_string(),
+            false,
+        );
+        self.max_db
+            .fill(crate::constants::audio::MIN_DB_LEVEL as f32);
+    }
+
+    /// Mark the instance offline and give queued messages a moment to go out
+    pub async fn shutdown(self) {
+        self.publish(&format!("{}/status", self.topic), "offline", true);
+        let _ = self.client.try_disconnect();
+        let mut event_loop = self.event_loop;
+        let flushed = tokio::time::timeout(
+            Duration::from_millis(crate::constants::mqtt::SHUTDOWN_TIMEOUT_MS),
+            &mut event_loop,
+        )
+        .await;
+        if flushed.is_err() {
+            event_loop.abort();
+        }
+    }
+
+    fn publish_channel(&self, index: usize, name: &str, payload: &str) {
+        let topic = format!(
+            "{}/channel/{}/{}",
+            self.topic,
+            self.channels[index].path(),
+            name
+        );
+        self.publish(&topic, payload, true);
+    }
+
+    fn publish(&self, topic: &str, payload: impl Into<Vec<u8>>, retain: bool) {
+        if let Err(e) = self
+            .client
+            .try_publish(topic, QoS::AtLeastOnce, retain, payload)
+        {
+            tracing::debug!(topic, error = %e, "Failed to queue MQTT message");
+        }
+    }
+}
+
+/// Split `host[:port]`, defaulting to the standard MQTT port
+fn parse_broker(broker: &str) -> AppResult<(String, u16)> {
+    let invalid = || {
+        AppError::Io(std::io::Error::other(format!(
+            "Invalid MQTT broker: {}",
+            broker
+        )))
+    };
+    match broker.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() => {
+            let port = port.parse().map_err(|_| invalid())?;
+            Ok((host.to_string(), port))
+        }
+        Some(_) => Err(invalid()),
+        None if broker.is_empty() => Err(invalid()),
+        None => Ok((broker.to_string(), crate::constants::mqtt::DEFAULT_PORT)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_broker() {
+        assert_eq!(
+            parse_broker("localhost").unwrap(),
+            ("localhost".to_string(), 1883)
+        );
+        assert_eq!(
+            parse_broker("10.0.0.2:8883").unwrap(),
+            ("10.0.0.2".to_string(), 8883)
+        );
+        assert!(parse_broker("localhost:mqtt").is_err());
+        assert!(parse_broker(":1883").is_err());
+        assert!(parse_broker("").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Open Sound Control output of levels and triggers
+//!
+//! Sends `/soundcheck/level/<channel>` with the display and raw level in dB
+//! at a fixed rate, and `/soundcheck/trigger` with the channel and level
+//! whenever a channel crosses the threshold. Channels taken from another
+//! device with `--channels <device>:<n>` are sent as
+//! `/soundcheck/level/<device>/<n>` and `/soundcheck/trigger/<device>`.
+
+use crate::channels::ChannelInfo;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// An OSC argument
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+}
+
+/// Encode an OSC message
+pub fn encode_message(address: &str, args: &[OscArg]) -> Vec<u8> {
+    let mut packet = Vec::new();
+    write_padded_str(&mut packet, address);
+
+    let mut type_tags = String::from(",");
+    for arg in args {
+        type_tags.push(match arg {
+            OscArg::Int(_) => 'i',
+            OscArg::Float(_) => 'f',
+        });
+    }
+    write_padded_str(&mut packet, &type_tags);
+
+    for arg in args {
+        match arg {
+            OscArg::Int(value) => packet.extend_from_slice(&value.to_be_bytes()),
+            OscArg::Float(value) => packet.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+    packet
+}
+
+/// Write a null-terminated string padded to a multiple of four bytes
+fn write_padded_str(packet: &mut Vec<u8>, value: &str) {
+    packet.extend_from_slice(value.as_bytes());
+    let padding = 4 - value.len() % 4;
+    packet.extend(std::iter::repeat_n(0, padding));
+}
+
+/// Sends levels and triggers to an OSC receiver
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    interval: Duration,
+    last_sent: Option<Instant>,
+    previously_reached: Vec<bool>,
+    /// Device and hardware channel index for each selected channel
+    channels: Vec<ChannelInfo>,
+}
+
+impl OscSender {
+    /// Resolve `target` (host:port) and open a socket to send from
+    pub fn new(target: &str, rate_hz: f32, channels: &[ChannelInfo]) -> AppResult<Self> {
+        let target = target.to_socket_addrs()?.next().ok_or_else(|| {
+            AppError::Io(std::io::Error::other(format!("Cannot resolve {}", target)))
+        })?;
+        let bind_addr = if target.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.set_nonblocking(true)?;
+        tracing::info!(%target, rate_hz, "Sending OSC");
+
+        Ok(Self {
+            socket,
+            target,
+            interval: Duration::from_secs_f32(1.0 / rate_hz),
+            last_sent: None,
+            previously_reached: vec![false; channels.len()],
+            channels: channels.to_vec(),
+        })
+    }
+
+    /// Send triggers for new threshold crossings and levels when due
+    ///
+    /// Crossings are ignored while the trigger is disarmed.
+    pub fn update(&mut self, state: &AppState, armed: bool, now: Instant) {
+        for (i, &reached) in state.threshold_reached.iter().enumerate() {
+            if reached && armed && !self.previously_reached[i] {
+                let address = match self.channels[i].device_key() {
+                    Some(device) => format!("/soundcheck/trigger/{}", device),
+                    None => "/soundcheck/trigger".to_string(),
+                };
+                self.send(
+                    &address,
+                    &[
+                        OscArg::Int(self.channels[i].channel as i32),
+                        OscArg::Float(state.current_db[i]),
+                    ],
+                );
+            }
+            self.previously_reached[i] = reached;
+        }
+
+        if self
+            .last_sent
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return;
+        }
+        self.last_sent = Some(now);
+        for (i, info) in self.channels.iter().enumerate() {
+            self.send(
+                &format!("/soundcheck/level/{}", info.path()),
+                &[
+                    OscArg::Float(state.display_db[i]),
+                    OscArg::Float(state.current_db[i]),
+                ],
+            );
+        }
+    }
+
+    fn send(&self, address: &str, args: &[OscArg]) {
+        let packet = encode_message(address, args);
+        if let Err(e) = self.socket.send_to(&packet, self.target) {
+            tracing::debug!(address, error = %e, "Failed to send OSC message");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_message() {
+        let packet = encode_message("/a", &[OscArg::Int(1), OscArg::Float(0.5)]);
+        assert_eq!(
+            packet,
+            vec![
+                b'/', b'a', 0, 0, // address
+                b',', b'i', b'f', 0, // type tags
+                0, 0, 0, 1, // int
+                0x3f, 0, 0, 0, // float
+            ]
+        );
+    }
+
+    #[test]
+    fn test_padding_always_terminates() {
+        assert_eq!(encode_message("/abc", &[]), b"/abc\0\0\0\0,\0\0\0".to_vec());
+    }
+}
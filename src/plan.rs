@@ -0,0 +1,651 @@
+//! Scripted test plans for the `plan` command
+//!
+//! A plan is a TOML file listing steps that run one after another: operator
+//! prompts, waiting for signal, level measurements, assertions and pauses.
+//! Every step that listens opens the input afresh through the same `App`
+//! machinery as `detect`, `stats` and `assert`.
+
+use crate::app::{App, ExitCode, Mode};
+use crate::channels::{self, ChannelSpec, Channels};
+use crate::checks::{self, CheckResult, Measurements, Range, Report, Tolerance};
+use crate::config::{CheckArgs, Config};
+use crate::error::{AppError, AppResult};
+use crate::simulate::Signal;
+use crate::stats::StatKind;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncBufReadExt;
+
+/// A test plan as written in its TOML file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Plan {
+    /// Title shown in the report
+    pub name: Option<String>,
+    /// Audio input device name; the default device if not given
+    pub device: Option<String>,
+    /// Generated signal to use instead of a device, as for `--simulate`
+    pub simulate: Option<String>,
+    /// Channels for steps that do not list their own, as for `--channels`
+    pub channels: Option<Vec<ChannelSpec>>,
+    /// Names for `channels`, as for `--labels`
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Skip the remaining steps after the first failure
+    #[serde(default)]
+    pub stop_on_failure: bool,
+    #[serde(rename = "step")]
+    pub steps: Vec<Step>,
+}
+
+/// One step of a plan, selected by its `type` key
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    /// Show a message and wait for the operator to press Enter
+    Prompt { message: String },
+    /// Wait until `channel` goes above `threshold` dB
+    Wait {
+        channel: ChannelSpec,
+        threshold: i32,
+        /// Fail the step after this many seconds without signal
+        timeout: Option<f32>,
+        /// Shown while waiting
+        message: Option<String>,
+    },
+    /// Capture for `seconds` and report a statistic, optionally within a range
+    Measure {
+        name: Option<String>,
+        channels: Option<Vec<ChannelSpec>>,
+        #[serde(default)]
+        labels: Vec<String>,
+        seconds: f32,
+        #[serde(default = "default_stat")]
+        stat: StatKind,
+        /// Inclusive `[low, high]` range in dB the statistic must lie in
+        between: Option<[f32; 2]>,
+    },
+    /// Capture for `seconds` and check it like the `assert` command
+    Assert {
+        name: Option<String>,
+        channels: Option<Vec<ChannelSpec>>,
+        #[serde(default)]
+        labels: Vec<String>,
+        seconds: f32,
+        peak_between: Option<[f32; 2]>,
+        noise_below: Option<f32>,
+        #[serde(default)]
+        no_clip: bool,
+        /// Frequency with tolerance, e.g. "1000±5"
+        freq: Option<String>,
+    },
+    /// Wait for `seconds` without listening
+    Pause { seconds: f32 },
+}
+
+fn default_stat() -> StatKind {
+    StatKind::Max
+}
+
+impl Plan {
+    /// Read and validate the plan at `path`
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let plan: Plan = toml::from_str(&text).map_err(|e| AppError::Plan(e.to_string()))?;
+        plan.validate()?;
+        Ok(plan)
+    }
+
+    fn validate(&self) -> AppResult<()> {
+        if self.steps.is_empty() {
+            return Err(AppError::Plan(
+                "The plan has no [[step]] entries".to_string(),
+            ));
+        }
+        self.signal()?;
+        let (specs, labels) = self.channels(&None, &[]);
+        check_labels(&specs, &labels).map_err(AppError::Plan)?;
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let invalid = |msg: String| AppError::Plan(format!("Step {}: {}", i + 1, msg));
+            let seconds = match step {
+                Step::Prompt { .. } => None,
+                Step::Wait { timeout, .. } => *timeout,
+                Step::Measure { seconds, .. }
+                | Step::Assert { seconds, .. }
+                | Step::Pause { seconds } => Some(*seconds),
+            };
+            if let Some(seconds) = seconds
+                && !(seconds > 0.0 && seconds.is_finite())
+            {
+                return Err(invalid(format!(
+                    "Seconds must be positive, got {}",
+                    seconds
+                )));
+            }
+
+            if let Step::Measure {
+                channels, labels, ..
+            }
+            | Step::Assert {
+                channels, labels, ..
+            } = step
+            {
+                let (specs, labels) = self.channels(channels, labels);
+                check_labels(&specs, &labels).map_err(invalid)?;
+            }
+
+            match step {
+                Step::Wait { channel, .. } if !matches!(channel.channels, Channels::Range { first, last } if first == last) =>
+                {
+                    return Err(invalid(format!(
+                        "Wait on a single channel, got {}",
+                        channel
+                    )));
+                }
+                Step::Wait { threshold, .. } if *threshold > 0 || *threshold < -60 => {
+                    return Err(invalid(format!(
+                        "Threshold must be between -60 and 0 dB, got {}",
+                        threshold
+                    )));
+                }
+                Step::Measure {
+                    between: Some(range),
+                    ..
+                } => {
+                    range_from(*range).map_err(invalid)?;
+                }
+                Step::Assert { .. } if self.checks(step).map_err(invalid)?.is_empty() => {
+                    return Err(invalid(
+                        "Nothing to check: set peak_between, noise_below, no_clip or freq"
+                            .to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn signal(&self) -> AppResult<Option<Signal>> {
+        self.simulate
+            .as_deref()
+            .map(|spec| spec.parse().map_err(AppError::Plan))
+            .transpose()
+    }
+
+    /// Channels a step listens on and their labels
+    ///
+    /// A step that lists its own channels only uses its own labels; one that
+    /// does not falls back to the plan's labels along with its channels.
+    fn channels(
+        &self,
+        step_channels: &Option<Vec<ChannelSpec>>,
+        step_labels: &[String],
+    ) -> (Vec<ChannelSpec>, Vec<String>) {
+        match step_channels {
+            Some(specs) => (specs.clone(), step_labels.to_vec()),
+            None => {
+                let specs = self
+                    .channels
+                    .clone()
+                    .unwrap_or_else(|| vec![ChannelSpec::from(0)]);
+                let labels = if step_labels.is_empty() {
+                    &self.labels
+                } else {
+                    step_labels
+                };
+                (specs, labels.to_vec())
+            }
+        }
+    }
+
+    /// Conditions of an assert step
+    fn checks(&self, step: &Step) -> Result<CheckArgs, String> {
+        let Step::Assert {
+            peak_between,
+            noise_below,
+            no_clip,
+            freq,
+            ..
+        } = step
+        else {
+            return Ok(CheckArgs::default());
+        };
+        Ok(CheckArgs {
+            peak_between: peak_between.map(range_from).transpose()?,
+            noise_below: *noise_below,
+            no_clip: *no_clip,
+            freq: freq.as_deref().map(str::parse::<Tolerance>).transpose()?,
+        })
+    }
+}
+
+/// Check the label count up front where it does not depend on a device
+///
+/// Channels selected with `all` are counted when the step runs.
+fn check_labels(specs: &[ChannelSpec], labels: &[String]) -> Result<(), String> {
+    if labels.is_empty() || specs.iter().any(|spec| spec.channels == Channels::All) {
+        return Ok(());
+    }
+    let count = channels::resolve(specs, |_| Ok(0))
+        .map_err(|e| e.to_string())?
+        .channels
+        .len();
+    if count != labels.len() {
+        return Err(format!(
+            "Got {} labels for {} channels",
+            labels.len(),
+            count
+        ));
+    }
+    Ok(())
+}
+
+fn range_from([low, high]: [f32; 2]) -> Result<Range, String> {
+    format!("{},{}", low, high).parse()
+}
+
+/// Results of one step
+#[derive(Debug, Serialize)]
+pub struct StepReport {
+    /// Position in the plan, from 1
+    pub step: usize,
+    pub kind: &'static str,
+    pub description: String,
+    /// `None` for steps that cannot fail, like prompts and pauses
+    pub passed: Option<bool>,
+    pub duration_s: f32,
+    pub checks: Vec<CheckResult>,
+}
+
+/// Results of a whole plan run
+#[derive(Debug, Serialize)]
+pub struct PlanReport {
+    pub name: Option<String>,
+    pub passed: bool,
+    /// False if the operator interrupted the plan
+    pub completed: bool,
+    pub duration_s: f32,
+    pub steps: Vec<StepReport>,
+}
+
+impl PlanReport {
+    /// Exit code for this run
+    pub fn exit_code(&self) -> ExitCode {
+        if !self.completed {
+            ExitCode::UserExit
+        } else if self.passed {
+            ExitCode::Success
+        } else {
+            ExitCode::CheckFailed
+        }
+    }
+
+    /// Write the report as JSON
+    pub fn write(&self, path: &Path) -> AppResult<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(file, self).map_err(std::io::Error::from)?;
+        Ok(())
+    }
+}
+
+/// How a step ended
+enum Outcome {
+    Done(Option<bool>, Vec<CheckResult>),
+    Interrupted,
+}
+
+/// Run every step of `plan`, printing progress as it goes
+///
+/// `device` and `simulate` override the plan's own input.
+pub async fn run(
+    plan: &Plan,
+    device: Option<String>,
+    simulate: Option<Signal>,
+) -> AppResult<PlanReport> {
+    let device = device.or_else(|| plan.device.clone());
+    let simulate = match simulate {
+        Some(signal) => Some(signal),
+        None if device.is_none() => plan.signal()?,
+        None => None,
+    };
+    let input = |specs: &[ChannelSpec], labels: &[String]| {
+        Config::for_channels(device.clone(), simulate.clone(), specs, labels)
+            .map_err(|e| AppError::Plan(e.to_string()))
+    };
+
+    if let Some(name) = &plan.name {
+        println!("{}", name);
+    }
+    let started = Instant::now();
+    let mut report = PlanReport {
+        name: plan.name.clone(),
+        passed: true,
+        completed: true,
+        duration_s: 0.0,
+        steps: Vec::with_capacity(plan.steps.len()),
+    };
+
+    for (i, step) in plan.steps.iter().enumerate() {
+        let (kind, description) = describe(step);
+        println!("[{}/{}] {}", i + 1, plan.steps.len(), description);
+        let step_started = Instant::now();
+
+        let outcome = match step {
+            Step::Prompt { message } => prompt(message).await?,
+            Step::Wait {
+                channel,
+                threshold,
+                timeout,
+                message,
+            } => {
+                if let Some(message) = message {
+                    println!("{}", message);
+                }
+                let mut config = input(std::slice::from_ref(channel), &[])?;
+                config.threshold_db = *threshold;
+                wait_for_signal(config, *threshold, *timeout).await?
+            }
+            Step::Measure {
+                channels,
+                labels,
+                seconds,
+                stat,
+                between,
+                ..
+            } => {
+                let (specs, labels) = plan.channels(channels, labels);
+                let config = input(&specs, &labels)?;
+                let channels = config.channel_info(&[]);
+                let mut app = App::new_headless(config);
+                let values = app.run_stats(Some(*seconds), &[*stat]).await?.remove(0);
+                let range = between
+                    .map(range_from)
+                    .transpose()
+                    .map_err(AppError::Plan)?;
+                let results = channels
+                    .into_iter()
+                    .zip(values)
+                    .map(|(info, value)| CheckResult {
+                        channel: info.channel,
+                        device: info.device_spec,
+                        label: info.label,
+                        name: stat.name(),
+                        passed: range.is_none_or(|r| (r.low..=r.high).contains(&value)),
+                        message: match range {
+                            Some(r) => format!(
+                                "{:.1} dB, expected between {:.1} and {:.1} dB",
+                                value, r.low, r.high
+                            ),
+                            None => format!("{:.1} dB", value),
+                        },
+                    })
+                    .collect();
+                Outcome::Done(range.map(|_| true), results)
+            }
+            Step::Assert {
+                channels,
+                labels,
+                seconds,
+                ..
+            } => {
+                let (specs, labels) = plan.channels(channels, labels);
+                let checks = plan.checks(step).map_err(AppError::Plan)?;
+                let config = input(&specs, &labels)?;
+                let channels = config.channel_info(&[]);
+                let mut app = App::new_headless(config);
+                let statistics = Measurements::statistics(channels.len());
+                let results = app.run_statistics(Some(*seconds), statistics).await?;
+                let measurements = Measurements::from_results(results);
+                Outcome::Done(
+                    Some(true),
+                    checks::evaluate(&checks, &channels, &measurements),
+                )
+            }
+            Step::Pause { seconds } => {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs_f32(*seconds)) => {
+                        Outcome::Done(None, Vec::new())
+                    }
+                    _ = tokio::signal::ctrl_c() => Outcome::Interrupted,
+                }
+            }
+        };
+
+        let Outcome::Done(passed, checks) = outcome else {
+            report.completed = false;
+            break;
+        };
+        // A step that can fail passes only if all of its checks do
+        let passed = passed.map(|_| checks.iter().all(|check| check.passed));
+        if !checks.is_empty() {
+            println!("{}", Report(&checks));
+        }
+
+        report.steps.push(StepReport {
+            step: i + 1,
+            kind,
+            description,
+            passed,
+            duration_s: step_started.elapsed().as_secs_f32(),
+            checks,
+        });
+        if passed == Some(false) {
+            report.passed = false;
+            if plan.stop_on_failure {
+                println!("Stopping after the first failure");
+                break;
+            }
+        }
+    }
+
+    report.duration_s = started.elapsed().as_secs_f32();
+    Ok(report)
+}
+
+/// Kind and one-line description of a step
+fn describe(step: &Step) -> (&'static str, String) {
+    match step {
+        Step::Prompt { .. } => ("prompt", "Operator prompt".to_string()),
+        Step::Wait {
+            channel, threshold, ..
+        } => (
+            "wait",
+            format!(
+                "Waiting for signal on channel {} above {} dB",
+                channel, threshold
+            ),
+        ),
+        Step::Measure {
+            name,
+            seconds,
+            stat,
+            ..
+        } => (
+            "measure",
+            name.clone()
+                .unwrap_or_else(|| format!("Measuring {} level for {} s", stat.name(), seconds)),
+        ),
+        Step::Assert { name, seconds, .. } => (
+            "assert",
+            name.clone()
+                .unwrap_or_else(|| format!("Checking levels for {} s", seconds)),
+        ),
+        Step::Pause { seconds } => ("pause", format!("Pausing for {} s", seconds)),
+    }
+}
+
+/// Show `message` and wait for Enter
+async fn prompt(message: &str) -> AppResult<Outcome> {
+    println!("{} [Enter]", message);
+    let mut line = String::new();
+    let mut stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    tokio::select! {
+        read = stdin.read_line(&mut line) => {
+            read?;
+            Ok(Outcome::Done(None, Vec::new()))
+        }
+        _ = tokio::signal::ctrl_c() => Ok(Outcome::Interrupted),
+    }
+}
+
+/// Run detect on one channel until it triggers or `timeout` passes
+async fn wait_for_signal(
+    config: Config,
+    threshold: i32,
+    timeout: Option<f32>,
+) -> AppResult<Outcome> {
+    let info = config.channel_info(&[]).remove(0);
+    let mut app = App::new_headless(config);
+    let started = Instant::now();
+    let detect = app.monitor(Mode::Detect, &mut []);
+    let run_result = match timeout {
+        Some(seconds) => tokio::time::timeout(Duration::from_secs_f32(seconds), detect)
+            .await
+            .ok(),
+        None => Some(detect.await),
+    };
+
+    let (passed, message) = match run_result {
+        Some(run_result) => {
+            run_result.result?;
+            if let ExitCode::UserExit = run_result.exit_code {
+                return Ok(Outcome::Interrupted);
+            }
+            (
+                true,
+                format!(
+                    "above {} dB after {:.1} s",
+                    threshold,
+                    started.elapsed().as_secs_f32()
+                ),
+            )
+        }
+        None => (
+            false,
+            format!(
+                "no signal above {} dB within {} s",
+                threshold,
+                timeout.unwrap_or_default()
+            ),
+        ),
+    };
+    Ok(Outcome::Done(
+        Some(passed),
+        vec![CheckResult {
+            channel: info.channel,
+            device: info.device_spec,
+            label: info.label,
+            name: "signal",
+            passed,
+            message,
+        }],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"
+        name = "Stage box"
+        simulate = "sine:1000:-12"
+        channels = [0, 1]
+        labels = ["Left", "Right"]
+
+        [[step]]
+        type = "prompt"
+        message = "Connect the oscillator"
+
+        [[step]]
+        type = "wait"
+        channel = 1
+        threshold = -30
+        timeout = 10
+
+        [[step]]
+        type = "measure"
+        seconds = 2
+        stat = "leq"
+        between = [-20, -6]
+
+        [[step]]
+        type = "assert"
+        channels = ["usb:0-1", 4]
+        labels = ["A", "B", "C"]
+        seconds = 1
+        no_clip = true
+        freq = "1000+-5"
+
+        [[step]]
+        type = "pause"
+        seconds = 0.5
+    "#;
+
+    #[test]
+    fn test_parse_plan() {
+        let plan: Plan = toml::from_str(PLAN).unwrap();
+        plan.validate().unwrap();
+        assert_eq!(plan.steps.len(), 5);
+        assert!(matches!(
+            plan.steps[2],
+            Step::Measure {
+                stat: StatKind::Leq,
+                ..
+            }
+        ));
+        let (specs, labels) = plan.channels(&None, &[]);
+        assert_eq!(specs, vec![ChannelSpec::from(0), ChannelSpec::from(1)]);
+        assert_eq!(labels, vec!["Left", "Right"]);
+
+        let Step::Assert {
+            channels, labels, ..
+        } = &plan.steps[3]
+        else {
+            panic!("expected an assert step");
+        };
+        let (specs, labels) = plan.channels(channels, labels);
+        assert_eq!(specs[0].to_string(), "usb:0-1");
+        assert_eq!(specs[1], ChannelSpec::from(4));
+        assert_eq!(labels.len(), 3);
+
+        let checks = plan.checks(&plan.steps[3]).unwrap();
+        assert!(checks.no_clip);
+        assert_eq!(checks.freq.map(|f| f.target), Some(1000.0));
+    }
+
+    #[test]
+    fn test_rejects_invalid_steps() {
+        let invalid = |steps: &str| {
+            toml::from_str::<Plan>(steps)
+                .map_err(|e| AppError::Plan(e.to_string()))
+                .and_then(|plan| plan.validate())
+                .is_err()
+        };
+        assert!(invalid("step = []"));
+        assert!(invalid("[[step]]\ntype = \"beep\""));
+        assert!(invalid("[[step]]\ntype = \"pause\"\nseconds = 0"));
+        assert!(invalid(
+            "[[step]]\ntype = \"pause\"\nseconds = 1\nextra = 1"
+        ));
+        assert!(invalid("[[step]]\ntype = \"assert\"\nseconds = 1"));
+        assert!(invalid(
+            "[[step]]\ntype = \"measure\"\nseconds = 1\nbetween = [-3, -20]"
+        ));
+        assert!(invalid(
+            "labels = [\"Kick\"]\nchannels = [\"0-1\"]\n[[step]]\ntype = \"pause\"\nseconds = 1"
+        ));
+        assert!(invalid(
+            "[[step]]\ntype = \"wait\"\nchannel = \"0-3\"\nthreshold = -30"
+        ));
+        assert!(invalid(
+            "[[step]]\ntype = \"measure\"\nseconds = 1\nchannels = [\"usb:x\"]"
+        ));
+        assert!(!invalid("[[step]]\ntype = \"pause\"\nseconds = 1"));
+        assert!(!invalid(
+            "channels = [\"all\"]\nlabels = [\"Kick\"]\n[[step]]\ntype = \"pause\"\nseconds = 1"
+        ));
+    }
+}
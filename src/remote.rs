@@ -0,0 +1,122 @@
+//! Level frames streamed over TCP for remote terminal meters
+//!
+//! `serve --listen` sends each client one `ui::UiState` as a JSON line per
+//! frame; `watch` connects to it and draws the frames with the regular UI.
+
+use crate::error::{AppError, AppResult};
+use crate::terminal::TerminalGuard;
+use crate::ui::{self, UiState};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+/// Stream level frames to every connected client until the task is dropped
+pub async fn serve(listener: TcpListener, ui_rx: watch::Receiver<UiState>) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                crate::http::accept_failed("watch", e).await;
+                continue;
+            }
+        };
+        tracing::info!(%peer, "Watch client connected");
+        let ui_rx = ui_rx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = stream_frames(stream, ui_rx).await {
+                tracing::debug!(%peer, error = %e, "Watch client disconnected");
+            }
+        });
+    }
+}
+
+/// Send UI snapshots as JSON lines until the client goes away
+async fn stream_frames(
+    mut stream: TcpStream,
+    mut ui_rx: watch::Receiver<UiState>,
+) -> std::io::Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_millis(
+        crate::constants::net::STREAM_INTERVAL_MS,
+    ));
+    loop {
+        interval.tick().await;
+        if ui_rx.has_changed().is_err() {
+            // Monitoring stopped
+            return Ok(());
+        }
+        let mut line =
+            serde_json::to_string(&*ui_rx.borrow_and_update()).map_err(std::io::Error::other)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).await?;
+    }
+}
+
+/// Draw the levels streamed by a remote `serve --listen` until Escape, q or Ctrl+C
+pub async fn watch(addr: &str) -> AppResult<()> {
+    let stream = TcpStream::connect(addr).await?;
+    tracing::info!(addr, "Watching remote levels");
+    let mut frames = BufReader::new(stream).lines();
+
+    // Restored when dropped, including on errors
+    let mut terminal = TerminalGuard::new()?;
+    let mut interval = tokio::time::interval(Duration::from_millis(
+        crate::constants::ui::UPDATE_INTERVAL_MS,
+    ));
+
+    loop {
+        tokio::select! {
+            line = frames.next_line() => {
+                let Some(line) = line? else {
+                    return Err(AppError::Io(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        format!("Connection closed by {}", addr),
+                    )));
+                };
+                let mut state: UiState = serde_json::from_str(&line).map_err(std::io::Error::other)?;
+                state.status = format!("{} [{}] Press Escape or q to quit.", state.status, addr);
+                terminal.draw(|f| ui::render_ui(f, &state))?;
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = interval.tick() => {}
+        }
+
+        // Check for keyboard events
+        if crossterm::event::poll(Duration::from_millis(0)).unwrap_or(false)
+            && let Ok(Event::Key(key_event)) = crossterm::event::read()
+        {
+            let ctrl_c = key_event.code == KeyCode::Char('c')
+                && key_event.modifiers.contains(KeyModifiers::CONTROL);
+            if ctrl_c || matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q')) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_streams_frames_as_json_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (ui_tx, ui_rx) = watch::channel(UiState {
+            device_name: "test_device".to_string(),
+            current_db: vec![-12.0, -30.0],
+            ..UiState::default()
+        });
+        tokio::spawn(serve(listener, ui_rx));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut frames = BufReader::new(stream).lines();
+        let line = frames.next_line().await.unwrap().unwrap();
+        let state: UiState = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(state.device_name, "test_device");
+        assert_eq!(state.current_db, vec![-12.0, -30.0]);
+        drop(ui_tx);
+    }
+}
@@ -0,0 +1,238 @@
+//! Live scan of every input device for the `scan` command
+//!
+//! Opens all input devices at once, meters every channel of each and
+//! highlights the channels that have signal, to find where a source arrives.
+
+use crate::app::Input;
+use crate::audio;
+use crate::config::ScanArgs;
+use crate::error::AppResult;
+use crate::simulate::Simulator;
+use crate::smoothing::Ballistics;
+use crate::state::SharedState;
+use crate::terminal::TerminalGuard;
+use crate::ui::{self, ScanChannel, ScanDevice, ScanState};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// One device being scanned
+struct Source {
+    name: String,
+    detail: String,
+    /// `None` if the device could not be opened
+    state: Option<SharedState>,
+    errors: Option<mpsc::Receiver<cpal::StreamError>>,
+    /// When each channel last had signal
+    last_active: Vec<Option<Instant>>,
+    _input: Option<Input>,
+}
+
+impl Source {
+    /// Open every channel of `device`, or record why it failed
+    fn open(device: &cpal::Device, ballistics: Ballistics) -> Self {
+        let name = device
+            .name()
+            .unwrap_or_else(|_| "Unknown device".to_string());
+        match Self::start(device, ballistics) {
+            Ok((state, errors, input, detail)) => Self {
+                last_active: vec![None; state.channels().len()],
+                name,
+                detail,
+                state: Some(state),
+                errors: Some(errors),
+                _input: Some(input),
+            },
+            Err(e) => {
+                tracing::warn!(device = %name, error = %e, "Could not scan device");
+                Self {
+                    name,
+                    detail: e.to_string(),
+                    state: None,
+                    errors: None,
+                    last_active: Vec::new(),
+                    _input: None,
+                }
+            }
+        }
+    }
+
+    fn start(
+        device: &cpal::Device,
+        ballistics: Ballistics,
+    ) -> AppResult<(
+        SharedState,
+        mpsc::Receiver<cpal::StreamError>,
+        Input,
+        String,
+    )> {
+        let mut audio_config = audio::device_config(device, &[])?;
+        let total_channels = audio_config.channels as usize;
+        audio_config.selected_channels = (0..total_channels).collect();
+
+        // Nothing triggers while scanning
+        let state = SharedState::new(total_channels, f32::INFINITY);
+        let callback = audio::create_audio_callback(
+            state.clone(),
+            ballistics,
+            &audio_config.selected_channels,
+            total_channels,
+            audio_config.sample_rate,
+        );
+        let stream_config = cpal::StreamConfig {
+            channels: audio_config.channels,
+            sample_rate: cpal::SampleRate(audio_config.sample_rate),
+            buffer_size: crate::constants::audio::BUFFER_SIZE,
+        };
+        let (error_tx, error_rx) = mpsc::channel();
+        let stream = audio::build_audio_stream(
+            device,
+            &stream_config,
+            audio::with_capture_clock(callback),
+            error_tx,
+        )?;
+        stream.play()?;
+
+        let detail = format!(
+            "{} channels, {} Hz",
+            total_channels, audio_config.sample_rate
+        );
+        Ok((state, error_rx, Input::Device(stream), detail))
+    }
+
+    /// Current meters, refreshing which channels have signal
+    fn snapshot(&mut self, threshold_db: f32, now: Instant) -> ScanDevice {
+        if let Some(err) = self.errors.as_ref().and_then(|rx| rx.try_iter().last()) {
+            tracing::warn!(device = %self.name, error = %err, "Stream error while scanning");
+            self.detail = format!("Stream error: {}", err);
+        }
+
+        let hold = Duration::from_secs_f32(crate::constants::scan::ACTIVE_HOLD_S);
+        let channels = match &self.state {
+            Some(state) => state
+                .channels()
+                .iter()
+                .zip(&mut self.last_active)
+                .map(|(levels, last_active)| {
+                    if has_signal(levels.current_db.load(), threshold_db) {
+                        *last_active = Some(now);
+                    }
+                    ScanChannel {
+                        display_db: levels.display_db.load(),
+                        active: last_active.is_some_and(|t| now.duration_since(t) < hold),
+                    }
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        ScanDevice {
+            name: self.name.clone(),
+            detail: self.detail.clone(),
+            channels,
+        }
+    }
+
+    /// Loudest level each channel reached during the scan
+    fn peaks(&self) -> Vec<f32> {
+        self.state
+            .iter()
+            .flat_map(|state| state.channels().iter().map(|levels| levels.peak_db.load()))
+            .collect()
+    }
+}
+
+/// Open every input device and meter all of their channels until quit
+///
+/// Prints the channels that had signal once the scan ends.
+pub async fn run(args: &ScanArgs) -> AppResult<()> {
+    let mut sources = match &args.simulate {
+        Some(signal) => vec![simulated_source(signal, args.ballistics)?],
+        None => cpal::default_host()
+            .input_devices()?
+            .map(|device| Source::open(&device, args.ballistics))
+            .collect(),
+    };
+    tracing::info!(devices = sources.len(), "Scanning input devices");
+
+    let mut terminal = TerminalGuard::new()?;
+    let mut interval = tokio::time::interval(Duration::from_millis(
+        crate::constants::ui::UPDATE_INTERVAL_MS,
+    ));
+    loop {
+        let now = Instant::now();
+        let state = ScanState {
+            devices: sources
+                .iter_mut()
+                .map(|source| source.snapshot(args.threshold as f32, now))
+                .collect(),
+            min_db: args.min_db,
+        };
+        terminal.draw(|f| ui::render_scan(f, &state))?;
+
+        let mut quit = false;
+        while crossterm::event::poll(Duration::from_millis(0))? {
+            if let Event::Key(key) = crossterm::event::read()? {
+                quit |= matches!(key.code, KeyCode::Esc | KeyCode::Char('q'))
+                    || (key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL));
+            }
+        }
+        if quit {
+            break;
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = interval.tick() => {}
+        }
+    }
+    drop(terminal);
+
+    let mut found = false;
+    for source in &sources {
+        for (ch, peak) in source.peaks().into_iter().enumerate() {
+            if has_signal(peak, args.threshold as f32) {
+                found = true;
+                println!("{}: channel {} peaked at {:.1} dB", source.name, ch, peak);
+            }
+        }
+    }
+    if !found {
+        println!("No signal above {} dB on any channel", args.threshold);
+    }
+    Ok(())
+}
+
+/// Whether `level_db` counts as signal at `threshold_db`
+///
+/// Silence reads as the meter floor, so only a rise above it is signal, even
+/// with a threshold at or below the floor.
+fn has_signal(level_db: f32, threshold_db: f32) -> bool {
+    level_db >= threshold_db && level_db > crate::constants::audio::MIN_DB_LEVEL as f32
+}
+
+/// A generated signal standing in for a device
+fn simulated_source(signal: &crate::simulate::Signal, ballistics: Ballistics) -> AppResult<Source> {
+    let total_channels = crate::constants::simulate::CHANNELS;
+    let sample_rate = crate::constants::simulate::SAMPLE_RATE;
+    let state = SharedState::new(total_channels, f32::INFINITY);
+    let channels: Vec<usize> = (0..total_channels).collect();
+    let callback = audio::create_audio_callback(
+        state.clone(),
+        ballistics,
+        &channels,
+        total_channels,
+        sample_rate,
+    );
+    let simulator = Simulator::start(signal, total_channels, sample_rate, callback)?;
+    Ok(Source {
+        name: signal.to_string(),
+        detail: format!("{} channels, {} Hz", total_channels, sample_rate),
+        last_active: vec![None; total_channels],
+        state: Some(state),
+        errors: None,
+        _input: Some(Input::Simulated(simulator)),
+    })
+}
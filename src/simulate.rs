@@ -0,0 +1,344 @@
+//! Deterministic synthetic input for testing without audio hardware
+//!
+//! A background thread generates buffers in real time and feeds them to the
+//! same audio callback a device stream would.
+
+use crate::clock::Clock;
+use crate::error::AppResult;
+use crate::smoothing::db_to_amplitude;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A generated test signal, parsed from `--simulate`
+///
+/// Specs take the form `kind[:params][:level_db]`, e.g. `sine:1000:-12`,
+/// `white:-20`, `pink`, `burst:1,2.5,4:-6` or `silence`. Levels are peak
+/// dBFS and default to -12.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Signal {
+    /// Sine wave at `frequency` Hz
+    Sine { frequency: f32, level_db: f32 },
+    /// Uniform white noise
+    White { level_db: f32 },
+    /// Pink (1/f) noise
+    Pink { level_db: f32 },
+    /// Short sine bursts starting at each of `times` seconds, silence otherwise
+    Bursts { times: Vec<f32>, level_db: f32 },
+    /// Digital silence
+    Silence,
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut parts = spec.split(':');
+        let kind = parts.next().unwrap_or_default();
+        let default_level = crate::constants::simulate::DEFAULT_LEVEL_DB;
+
+        let signal = match kind {
+            "sine" => {
+                let frequency = number(parts.next(), "frequency", spec)?
+                    .ok_or_else(|| format!("Missing frequency in {:?}, e.g. sine:1000", spec))?;
+                Signal::Sine {
+                    frequency,
+                    level_db: number(parts.next(), "level", spec)?.unwrap_or(default_level),
+                }
+            }
+            "white" => Signal::White {
+                level_db: number(parts.next(), "level", spec)?.unwrap_or(default_level),
+            },
+            "pink" => Signal::Pink {
+                level_db: number(parts.next(), "level", spec)?.unwrap_or(default_level),
+            },
+            "burst" => {
+                let times = parts
+                    .next()
+                    .ok_or_else(|| format!("Missing burst times in {:?}, e.g. burst:1,2.5", spec))?
+                    .split(',')
+                    .map(|t| {
+                        t.parse::<f32>()
+                            .ok()
+                            .filter(|t| *t >= 0.0)
+                            .ok_or_else(|| format!("Invalid burst time in {:?}: {}", spec, t))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Signal::Bursts {
+                    times,
+                    level_db: number(parts.next(), "level", spec)?.unwrap_or(default_level),
+                }
+            }
+            "silence" => Signal::Silence,
+            _ => {
+                return Err(format!(
+                    "Unknown signal {:?}: expected sine, white, pink, burst or silence",
+                    kind
+                ));
+            }
+        };
+
+        if parts.next().is_some() {
+            return Err(format!("Too many fields in {:?}", spec));
+        }
+        if let Signal::Sine { frequency, .. } = signal
+            && frequency <= 0.0
+        {
+            return Err(format!("Frequency must be positive in {:?}", spec));
+        }
+        Ok(signal)
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Sine {
+                frequency,
+                level_db,
+            } => write!(f, "Simulated {} Hz sine at {} dB", frequency, level_db),
+            Signal::White { level_db } => write!(f, "Simulated white noise at {} dB", level_db),
+            Signal::Pink { level_db } => write!(f, "Simulated pink noise at {} dB", level_db),
+            Signal::Bursts { times, level_db } => {
+                write!(f, "Simulated {} bursts at {} dB", times.len(), level_db)
+            }
+            Signal::Silence => write!(f, "Simulated silence"),
+        }
+    }
+}
+
+/// Produces the samples of a signal one at a time
+pub struct Generator {
+    signal: Signal,
+    amplitude: f32,
+    sample_rate: u32,
+    frame: u64,
+    rng: u32,
+    pink: [f32; 7],
+}
+
+impl Generator {
+    /// Start generating `signal` at time zero
+    pub fn new(signal: Signal, sample_rate: u32) -> Self {
+        let amplitude = match &signal {
+            Signal::Sine { level_db, .. }
+            | Signal::White { level_db }
+            | Signal::Pink { level_db }
+            | Signal::Bursts { level_db, .. } => db_to_amplitude(*level_db).min(1.0),
+            Signal::Silence => 0.0,
+        };
+        Self {
+            signal,
+            amplitude,
+            sample_rate,
+            frame: 0,
+            rng: crate::constants::simulate::NOISE_SEED,
+            pink: [0.0; 7],
+        }
+    }
+
+    /// Next sample of the signal
+    pub fn next_sample(&mut self) -> f32 {
+        let t = self.frame as f64 / self.sample_rate as f64;
+        self.frame += 1;
+
+        let value = match &self.signal {
+            Signal::Sine { frequency, .. } => sine(*frequency as f64, t),
+            Signal::White { .. } => self.white(),
+            Signal::Pink { .. } => self.pink(),
+            Signal::Bursts { times, .. } => {
+                let burst_s = crate::constants::simulate::BURST_S;
+                if times
+                    .iter()
+                    .any(|&start| t >= start as f64 && t < start as f64 + burst_s)
+                {
+                    sine(crate::constants::simulate::BURST_FREQUENCY_HZ, t)
+                } else {
+                    0.0
+                }
+            }
+            Signal::Silence => 0.0,
+        };
+        value * self.amplitude
+    }
+
+    /// Uniform noise in [-1, 1) from a xorshift generator, identical every run
+    fn white(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        (self.rng as f64 / u32::MAX as f64 * 2.0 - 1.0) as f32
+    }
+
+    /// White noise through Paul Kellet's pinking filter
+    fn pink(&mut self) -> f32 {
+        let white = self.white();
+        let b = &mut self.pink;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b.iter().sum::<f32>() + white * 0.5362;
+        b[6] = white * 0.115926;
+        (pink * 0.11).clamp(-1.0, 1.0)
+    }
+}
+
+/// Parse an optional numeric field of a signal spec
+fn number(field: Option<&str>, what: &str, spec: &str) -> Result<Option<f32>, String> {
+    field
+        .map(|value| {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid {} in {:?}: {}", what, spec, value))
+        })
+        .transpose()
+}
+
+fn sine(frequency: f64, t: f64) -> f32 {
+    (2.0 * std::f64::consts::PI * frequency * t).sin() as f32
+}
+
+/// Feeds a generated signal to an audio callback in real time
+///
+/// The generating thread stops when the simulator is dropped.
+pub struct Simulator {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    clock: Clock,
+}
+
+impl Simulator {
+    /// Start generating `signal` on every one of `total_channels` channels
+    pub fn start<F>(
+        signal: &Signal,
+        total_channels: usize,
+        sample_rate: u32,
+        mut callback: F,
+    ) -> AppResult<Self>
+    where
+        F: FnMut(&[f32], Option<Duration>) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let frames = Arc::new(AtomicU64::new(0));
+        let start = Instant::now();
+        let clock = Clock::Samples {
+            start,
+            frames: frames.clone(),
+            sample_rate,
+        };
+
+        let mut generator = Generator::new(signal.clone(), sample_rate);
+        let frames_per_buffer =
+            (sample_rate as u64 * crate::constants::simulate::BUFFER_MS / 1000) as usize;
+        let thread_stop = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("simulate".to_string())
+            .spawn(move || {
+                let mut buffer = vec![0.0f32; frames_per_buffer * total_channels];
+                let mut generated = 0u64;
+                while !thread_stop.load(Ordering::Relaxed) {
+                    for frame in buffer.chunks_exact_mut(total_channels) {
+                        frame.fill(generator.next_sample());
+                    }
+                    let capture = Duration::from_secs_f64(generated as f64 / sample_rate as f64);
+                    callback(&buffer, Some(capture));
+                    generated += frames_per_buffer as u64;
+                    frames.store(generated, Ordering::Relaxed);
+
+                    // Pace generation to real time
+                    let due =
+                        start + Duration::from_secs_f64(generated as f64 / sample_rate as f64);
+                    if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                        std::thread::sleep(wait);
+                    }
+                }
+            })?;
+        tracing::info!(%signal, total_channels, sample_rate, "Started simulated input");
+
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+            clock,
+        })
+    }
+
+    /// Clock advanced by the generated samples
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(generator: &mut Generator, frames: usize) -> f32 {
+        (0..frames).fold(0.0f32, |peak, _| peak.max(generator.next_sample().abs()))
+    }
+
+    #[test]
+    fn test_parse_signals() {
+        assert_eq!(
+            "sine:1000".parse(),
+            Ok(Signal::Sine {
+                frequency: 1000.0,
+                level_db: -12.0
+            })
+        );
+        assert_eq!("pink:-20".parse(), Ok(Signal::Pink { level_db: -20.0 }));
+        assert_eq!(
+            "burst:1,2.5:-6".parse(),
+            Ok(Signal::Bursts {
+                times: vec![1.0, 2.5],
+                level_db: -6.0
+            })
+        );
+        assert_eq!("silence".parse(), Ok(Signal::Silence));
+        assert!("sine".parse::<Signal>().is_err());
+        assert!("sine:0".parse::<Signal>().is_err());
+        assert!("white:-20:5".parse::<Signal>().is_err());
+        assert!("square:440".parse::<Signal>().is_err());
+    }
+
+    #[test]
+    fn test_sine_reaches_its_level() {
+        let mut generator = Generator::new("sine:1000:-6".parse().unwrap(), 48000);
+        let peak_db = 20.0 * peak(&mut generator, 4800).log10();
+        assert!((peak_db + 6.0).abs() < 0.01, "peak {} dB", peak_db);
+    }
+
+    #[test]
+    fn test_bursts_follow_schedule() {
+        let mut generator = Generator::new("burst:0.5".parse().unwrap(), 48000);
+        assert_eq!(peak(&mut generator, 24000), 0.0);
+        assert!(peak(&mut generator, 4800) > 0.2);
+        assert_eq!(peak(&mut generator, 19200), 0.0);
+    }
+
+    #[test]
+    fn test_noise_is_deterministic() {
+        let mut first = Generator::new(Signal::Pink { level_db: 0.0 }, 48000);
+        let mut second = Generator::new(Signal::Pink { level_db: 0.0 }, 48000);
+        for _ in 0..1000 {
+            assert_eq!(first.next_sample(), second.next_sample());
+        }
+        assert!(peak(&mut first, 48000) > 0.1);
+    }
+}
@@ -1,6 +1,148 @@
 //! Audio level smoothing and processing utilities
+//!
+//! Meter ballistics are expressed as time constants and release rates so the
+//! meter moves at the same speed regardless of the device's buffer size or
+//! sample rate.
+
+use clap::ValueEnum;
 
 /// Convert decibels to linear amplitude
 pub fn db_to_amplitude(db: f32) -> f32 {
     10.0f32.powf(db / 20.0)
 }
+
+/// How a meter reading moves towards a new level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Response {
+    /// Jump to the new level immediately
+    Instant,
+    /// Exponential approach with the given time constant in seconds
+    TimeConstant(f32),
+    /// Fall at a fixed rate in dB per second
+    Linear(f32),
+}
+
+impl Response {
+    /// Move `previous_db` towards `target_db` over `dt` seconds
+    fn step(self, previous_db: f32, target_db: f32, dt: f32) -> f32 {
+        match self {
+            Response::Instant => target_db,
+            Response::TimeConstant(tau) => {
+                let coefficient = 1.0 - (-dt / tau).exp();
+                previous_db + (target_db - previous_db) * coefficient
+            }
+            Response::Linear(db_per_second) => {
+                let step = db_per_second * dt;
+                if previous_db > target_db {
+                    (previous_db - step).max(target_db)
+                } else {
+                    (previous_db + step).min(target_db)
+                }
+            }
+        }
+    }
+}
+
+/// Meter ballistics presets selectable with `--ballistics`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Ballistics {
+    /// Two-stage smoothing tuned for a calm terminal display
+    #[default]
+    Smooth,
+    /// VU meter: 300 ms rise and fall
+    Vu,
+    /// DIN 45406 peak programme meter: 5 ms attack, 20 dB in 1.5 s release
+    #[value(name = "ppm1")]
+    PpmType1,
+    /// BBC peak programme meter: 10 ms attack, 24 dB in 2.8 s release
+    #[value(name = "ppm2")]
+    PpmType2,
+    /// EBU digital peak meter: instant attack, 20 dB in 1.7 s release
+    Digital,
+}
+
+impl Ballistics {
+    /// Response used when the level rises
+    pub fn attack(self) -> Response {
+        use crate::constants::smoothing::DISPLAY_SMOOTHING_TIME_S;
+        match self {
+            Ballistics::Smooth => Response::TimeConstant(DISPLAY_SMOOTHING_TIME_S),
+            Ballistics::Vu => Response::TimeConstant(0.065),
+            Ballistics::PpmType1 => Response::TimeConstant(0.0017),
+            Ballistics::PpmType2 => Response::TimeConstant(0.0034),
+            Ballistics::Digital => Response::Instant,
+        }
+    }
+
+    /// Response used when the level falls
+    pub fn release(self) -> Response {
+        use crate::constants::smoothing::DISPLAY_SMOOTHING_TIME_S;
+        match self {
+            Ballistics::Smooth => Response::TimeConstant(DISPLAY_SMOOTHING_TIME_S),
+            Ballistics::Vu => Response::TimeConstant(0.065),
+            Ballistics::PpmType1 => Response::Linear(20.0 / 1.5),
+            Ballistics::PpmType2 => Response::Linear(24.0 / 2.8),
+            Ballistics::Digital => Response::Linear(20.0 / 1.7),
+        }
+    }
+
+    /// Whether the meter follows the pre-smoothed level instead of the raw peak
+    pub fn uses_smoothed_input(self) -> bool {
+        self == Ballistics::Smooth
+    }
+
+    /// Advance a meter reading by `dt` seconds towards `input_db`
+    pub fn step(self, previous_db: f32, input_db: f32, dt: f32) -> f32 {
+        let response = if input_db > previous_db {
+            self.attack()
+        } else {
+            self.release()
+        };
+        response.step(previous_db, input_db, dt)
+    }
+}
+
+/// First-stage smoothing applied to the raw level before the meter ballistics
+pub fn smooth_level(previous_db: f32, input_db: f32, dt: f32) -> f32 {
+    Response::TimeConstant(crate::constants::smoothing::AUDIO_SMOOTHING_TIME_S).step(
+        previous_db,
+        input_db,
+        dt,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the meter for one second of constant input using buffers of `dt`
+    fn settle(ballistics: Ballistics, from_db: f32, to_db: f32, dt: f32) -> f32 {
+        let steps = (1.0 / dt).round() as usize;
+        (0..steps).fold(from_db, |level, _| ballistics.step(level, to_db, dt))
+    }
+
+    #[test]
+    fn test_time_constant_independent_of_buffer_size() {
+        let small = settle(Ballistics::Vu, -60.0, 0.0, 64.0 / 48000.0 * 3.0);
+        let large = settle(Ballistics::Vu, -60.0, 0.0, 1024.0 / 48000.0 * 3.0);
+        assert!((small - large).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_digital_release_rate() {
+        // Instant attack
+        assert_eq!(Ballistics::Digital.step(-60.0, -6.0, 0.001), -6.0);
+
+        // 20 dB fall takes 1.7 seconds regardless of buffer size
+        let mut level = 0.0;
+        for _ in 0..170 {
+            level = Ballistics::Digital.step(level, -60.0, 0.01);
+        }
+        assert!((level + 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_linear_release_stops_at_target() {
+        assert_eq!(Response::Linear(10.0).step(-10.0, -12.0, 1.0), -12.0);
+    }
+}
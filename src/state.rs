@@ -1,14 +1,108 @@
 //! Application state management
 
-use std::sync::{Arc, Mutex};
+use crate::events::LevelEvent;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::time::Instant;
 
-/// Type alias for audio processing shared state references
-pub type AudioStateRefs = (
-    Arc<Mutex<Vec<f32>>>,
-    Arc<Mutex<Vec<f32>>>,
-    Arc<Mutex<Vec<f32>>>,
-    Arc<Mutex<Vec<bool>>>,
-);
+/// An `f32` that can be shared between threads without locking
+#[derive(Debug)]
+pub struct AtomicF32(AtomicU32);
+
+impl AtomicF32 {
+    /// Create a new atomic holding `value`
+    pub fn new(value: f32) -> Self {
+        Self(AtomicU32::new(value.to_bits()))
+    }
+
+    /// Read the current value
+    pub fn load(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Replace the current value
+    pub fn store(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// An `f64` that can be shared between threads without locking
+#[derive(Debug)]
+pub struct AtomicF64(AtomicU64);
+
+impl AtomicF64 {
+    /// Create a new atomic holding `value`
+    pub fn new(value: f64) -> Self {
+        Self(AtomicU64::new(value.to_bits()))
+    }
+
+    /// Read the current value
+    pub fn load(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Replace the current value
+    pub fn store(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Levels for one monitored channel, written by the audio thread
+#[derive(Debug)]
+pub struct ChannelLevels {
+    pub current_db: AtomicF32,
+    pub smoothed_db: AtomicF32,
+    pub display_db: AtomicF32,
+    pub threshold_reached: AtomicBool,
+    /// Highest buffer peak since monitoring started or peaks were reset
+    pub peak_db: AtomicF32,
+    /// RMS level of the most recent buffer
+    pub rms_db: AtomicF32,
+    /// Number of threshold crossings
+    pub trigger_count: AtomicU64,
+    /// Number of clip events
+    pub clip_count: AtomicU64,
+    /// Sum of squared samples since the stream started
+    pub sum_of_squares: AtomicF64,
+    /// Number of samples included in `sum_of_squares`
+    pub sample_count: AtomicU64,
+    /// Number of times the signal crossed zero since the stream started
+    pub zero_crossings: AtomicU64,
+    /// Sample rate of the device feeding this channel, or 0 before it starts
+    pub sample_rate: AtomicU32,
+}
+
+impl ChannelLevels {
+    fn new() -> Self {
+        let default_db = crate::constants::audio::MIN_DB_LEVEL as f32;
+        Self {
+            current_db: AtomicF32::new(default_db),
+            smoothed_db: AtomicF32::new(default_db),
+            display_db: AtomicF32::new(default_db),
+            threshold_reached: AtomicBool::new(false),
+            peak_db: AtomicF32::new(default_db),
+            rms_db: AtomicF32::new(default_db),
+            trigger_count: AtomicU64::new(0),
+            clip_count: AtomicU64::new(0),
+            sum_of_squares: AtomicF64::new(0.0),
+            sample_count: AtomicU64::new(0),
+            zero_crossings: AtomicU64::new(0),
+            sample_rate: AtomicU32::new(0),
+        }
+    }
+}
+
+/// Audio callback timing, for diagnostics
+#[derive(Debug, Default)]
+pub struct CallbackTiming {
+    /// Number of callbacks since the stream started
+    pub count: AtomicU64,
+    /// Frames in the most recent buffer
+    pub frames: AtomicU64,
+    /// Longest gap between two callbacks in microseconds since last read
+    pub max_interval_us: AtomicU64,
+}
 
 /// Internal application state
 pub struct AppState {
@@ -19,6 +113,12 @@ pub struct AppState {
     pub threshold_db: i32,
     pub status: String,
     pub threshold_reached: Vec<bool>,
+    pub sum_of_squares: Vec<f64>,
+    pub sample_count: Vec<u64>,
+    pub zero_crossings: Vec<u64>,
+    pub clip_count: Vec<u64>,
+    /// Sample rate feeding each channel, or 0 before its input has started
+    pub sample_rates: Vec<u32>,
 }
 
 impl AppState {
@@ -36,51 +136,131 @@ impl AppState {
                 device_name
             ),
             threshold_reached: vec![false; num_channels],
+            sum_of_squares: vec![0.0; num_channels],
+            sample_count: vec![0; num_channels],
+            zero_crossings: vec![0; num_channels],
+            clip_count: vec![0; num_channels],
+            sample_rates: vec![0; num_channels],
         }
     }
 
     /// Update state from shared audio processing values
-    pub fn update_from_audio(
-        &mut self,
-        current_db: &Arc<Mutex<Vec<f32>>>,
-        smoothed_db: &Arc<Mutex<Vec<f32>>>,
-        display_db: &Arc<Mutex<Vec<f32>>>,
-        threshold_reached: &Arc<Mutex<Vec<bool>>>,
-    ) {
-        self.current_db = current_db.lock().unwrap().clone();
-        self.smoothed_db = smoothed_db.lock().unwrap().clone();
-        self.display_db = display_db.lock().unwrap().clone();
-        self.threshold_reached = threshold_reached.lock().unwrap().clone();
+    ///
+    /// `threshold_reached` reports crossings since the previous update.
+    pub fn update_from_audio(&mut self, shared_state: &SharedState) {
+        for (i, levels) in shared_state.channels().iter().enumerate() {
+            self.current_db[i] = levels.current_db.load();
+            self.smoothed_db[i] = levels.smoothed_db.load();
+            self.display_db[i] = levels.display_db.load();
+            self.threshold_reached[i] = levels.threshold_reached.swap(false, Ordering::Relaxed);
+            self.sum_of_squares[i] = levels.sum_of_squares.load();
+            self.sample_count[i] = levels.sample_count.load(Ordering::Relaxed);
+            self.zero_crossings[i] = levels.zero_crossings.load(Ordering::Relaxed);
+            self.clip_count[i] = levels.clip_count.load(Ordering::Relaxed);
+            self.sample_rates[i] = levels.sample_rate.load(Ordering::Relaxed);
+        }
     }
 }
 
 /// Thread-safe shared state wrapper
+///
+/// Every value is an atomic so the audio callback never blocks on the UI.
+#[derive(Clone)]
 pub struct SharedState {
-    pub current_db: Arc<Mutex<Vec<f32>>>,
-    pub smoothed_db: Arc<Mutex<Vec<f32>>>,
-    pub display_db: Arc<Mutex<Vec<f32>>>,
-    pub threshold_reached: Arc<Mutex<Vec<bool>>>,
+    channels: Arc<[ChannelLevels]>,
+    /// Timing of the callbacks feeding each channel
+    timing: Arc<[CallbackTiming]>,
+    stream_errors: Arc<AtomicU64>,
+    /// Threshold as a linear amplitude, changeable while streaming
+    threshold: Arc<AtomicF32>,
+    origin: Instant,
+    events: Option<SyncSender<LevelEvent>>,
 }
 
 impl SharedState {
     /// Create new shared state with default values
-    pub fn new(num_channels: usize) -> Self {
-        let default_db = crate::constants::audio::MIN_DB_LEVEL as f32;
+    pub fn new(num_channels: usize, linear_threshold: f32) -> Self {
         Self {
-            current_db: Arc::new(Mutex::new(vec![default_db; num_channels])),
-            smoothed_db: Arc::new(Mutex::new(vec![default_db; num_channels])),
-            display_db: Arc::new(Mutex::new(vec![default_db; num_channels])),
-            threshold_reached: Arc::new(Mutex::new(vec![false; num_channels])),
+            channels: (0..num_channels).map(|_| ChannelLevels::new()).collect(),
+            timing: (0..num_channels)
+                .map(|_| CallbackTiming::default())
+                .collect(),
+            stream_errors: Arc::new(AtomicU64::new(0)),
+            threshold: Arc::new(AtomicF32::new(linear_threshold)),
+            origin: Instant::now(),
+            events: None,
+        }
+    }
+
+    /// Threshold as a linear amplitude, read by the audio callback
+    pub fn threshold(&self) -> &AtomicF32 {
+        &self.threshold
+    }
+
+    /// Forget the held peak of every channel
+    pub fn reset_peaks(&self) {
+        for levels in self.channels.iter() {
+            levels
+                .peak_db
+                .store(crate::constants::audio::MIN_DB_LEVEL as f32);
         }
     }
 
-    /// Get clones of all shared state references for audio processing
-    pub fn audio_refs(&self) -> AudioStateRefs {
-        (
-            Arc::clone(&self.current_db),
-            Arc::clone(&self.smoothed_db),
-            Arc::clone(&self.display_db),
-            Arc::clone(&self.threshold_reached),
-        )
+    /// Number of errors reported by the audio stream
+    pub fn stream_errors(&self) -> &AtomicU64 {
+        &self.stream_errors
+    }
+
+    /// Start queueing level events from the audio thread
+    ///
+    /// Events are dropped rather than blocking the audio thread if the
+    /// receiver falls behind.
+    pub fn enable_events(&mut self) -> Receiver<LevelEvent> {
+        let (tx, rx) = mpsc::sync_channel(crate::constants::events::QUEUE_CAPACITY);
+        self.events = Some(tx);
+        rx
+    }
+
+    /// Queue for level events, if enabled
+    pub fn events(&self) -> Option<&SyncSender<LevelEvent>> {
+        self.events.as_ref()
+    }
+
+    /// When monitoring started; event timestamps are relative to this
+    pub fn origin(&self) -> Instant {
+        self.origin
+    }
+
+    /// Per-channel levels, in the order of the selected channels
+    pub fn channels(&self) -> &[ChannelLevels] {
+        &self.channels
+    }
+
+    /// Timing of the audio callbacks feeding each channel
+    ///
+    /// Channels from the same device share their callback, so they hold the
+    /// same timing.
+    pub fn timing(&self) -> &[CallbackTiming] {
+        &self.timing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_from_audio_copies_levels() {
+        let shared_state = SharedState::new(2, 1.0);
+        shared_state.channels()[1].current_db.store(-12.5);
+        shared_state.channels()[1]
+            .threshold_reached
+            .store(true, Ordering::Relaxed);
+
+        let mut app_state = AppState::new("test_device".to_string(), 0, 2);
+        app_state.update_from_audio(&shared_state);
+
+        assert_eq!(app_state.current_db, vec![-60.0, -12.5]);
+        assert_eq!(app_state.threshold_reached, vec![false, true]);
     }
 }
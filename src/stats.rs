@@ -0,0 +1,593 @@
+//! Statistics accumulated over a monitoring session
+
+use crate::state::AppState;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Accumulates a per-channel statistic from level snapshots
+pub trait Statistic {
+    /// Feed one snapshot of the monitored levels
+    fn update(&mut self, state: &AppState);
+
+    /// Final value for each channel in dB
+    fn finish(&self) -> Vec<f32>;
+}
+
+/// Statistics selectable with `--stat` or in a test plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatKind {
+    Min,
+    Max,
+    Mean,
+    Median,
+    P95,
+    Stddev,
+    /// Energy average of every sample (mean square in dB)
+    Leq,
+    /// Mean of the buffer peak amplitudes in dB
+    PeakMean,
+}
+
+impl StatKind {
+    /// Create an empty accumulator for this statistic
+    pub fn build(self, num_channels: usize) -> Box<dyn Statistic> {
+        match self {
+            StatKind::Min => Box::new(Min::new(num_channels)),
+            StatKind::Max => Box::new(Max::new(num_channels)),
+            StatKind::Mean => Box::new(Mean::new(num_channels)),
+            StatKind::Median => Box::new(Percentile::new(num_channels, 50.0)),
+            StatKind::P95 => Box::new(Percentile::new(num_channels, 95.0)),
+            StatKind::Stddev => Box::new(StdDev::new(num_channels)),
+            StatKind::Leq => Box::new(Leq::new(num_channels)),
+            StatKind::PeakMean => Box::new(PeakMean::new(num_channels)),
+        }
+    }
+
+    /// Name used on the command line and in reports
+    pub fn name(self) -> &'static str {
+        match self {
+            StatKind::Min => "min",
+            StatKind::Max => "max",
+            StatKind::Mean => "mean",
+            StatKind::Median => "median",
+            StatKind::P95 => "p95",
+            StatKind::Stddev => "stddev",
+            StatKind::Leq => "leq",
+            StatKind::PeakMean => "peak-mean",
+        }
+    }
+}
+
+/// Averaging methods selectable with `average --method`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AverageMethod {
+    /// Equivalent continuous level: mean square of every sample in dB
+    #[default]
+    Leq,
+    /// Mean of the buffer peak amplitudes, converted to dB
+    PeakMean,
+    /// Mean of the buffer peak levels in dB (the original behavior)
+    DbMean,
+}
+
+impl AverageMethod {
+    /// Statistic that implements this method
+    pub fn stat_kind(self) -> StatKind {
+        match self {
+            AverageMethod::Leq => StatKind::Leq,
+            AverageMethod::PeakMean => StatKind::PeakMean,
+            AverageMethod::DbMean => StatKind::Mean,
+        }
+    }
+}
+
+/// Convert linear amplitude to dB, floored well below the meter's range
+///
+/// Quiet rooms measure below the display floor, so this uses the floor of
+/// the offline analysis rather than `MIN_DB_LEVEL`.
+fn amplitude_to_db(amplitude: f64) -> f32 {
+    let floor = crate::constants::analyze::FLOOR_DB;
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10() as f32).max(floor)
+    } else {
+        floor
+    }
+}
+
+/// Lowest current level seen on each channel
+pub struct Min {
+    levels: Vec<f32>,
+}
+
+impl Min {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            levels: vec![f32::INFINITY; num_channels],
+        }
+    }
+}
+
+impl Statistic for Min {
+    fn update(&mut self, state: &AppState) {
+        for (min, &current) in self.levels.iter_mut().zip(&state.current_db) {
+            *min = min.min(current);
+        }
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        let floor = crate::constants::audio::MIN_DB_LEVEL as f32;
+        self.levels
+            .iter()
+            .map(|&l| if l.is_finite() { l } else { floor })
+            .collect()
+    }
+}
+
+/// Highest current level seen on each channel
+pub struct Max {
+    levels: Vec<f32>,
+}
+
+impl Max {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            levels: vec![crate::constants::audio::MIN_DB_LEVEL as f32; num_channels],
+        }
+    }
+}
+
+impl Statistic for Max {
+    fn update(&mut self, state: &AppState) {
+        for (max, &current) in self.levels.iter_mut().zip(&state.current_db) {
+            *max = max.max(current);
+        }
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        self.levels.clone()
+    }
+}
+
+/// Arithmetic mean of the current level in dB
+pub struct Mean {
+    sums: Vec<f32>,
+    count: u32,
+}
+
+impl Mean {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            sums: vec![0.0; num_channels],
+            count: 0,
+        }
+    }
+}
+
+impl Statistic for Mean {
+    fn update(&mut self, state: &AppState) {
+        for (sum, &current) in self.sums.iter_mut().zip(&state.current_db) {
+            *sum += current;
+        }
+        self.count += 1;
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        self.sums
+            .iter()
+            .map(|&sum| {
+                if self.count > 0 {
+                    sum / self.count as f32
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
+/// Percentile of the current level, e.g. 50 for the median
+pub struct Percentile {
+    samples: Vec<Vec<f32>>,
+    percentile: f32,
+}
+
+impl Percentile {
+    pub fn new(num_channels: usize, percentile: f32) -> Self {
+        Self {
+            samples: vec![Vec::new(); num_channels],
+            percentile,
+        }
+    }
+}
+
+impl Statistic for Percentile {
+    fn update(&mut self, state: &AppState) {
+        for (samples, &current) in self.samples.iter_mut().zip(&state.current_db) {
+            samples.push(current);
+        }
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        self.samples
+            .iter()
+            .map(|samples| percentile(samples, self.percentile))
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of `values`
+pub fn percentile(values: &[f32], percentile: f32) -> f32 {
+    if values.is_empty() {
+        return crate::constants::audio::MIN_DB_LEVEL as f32;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let rank = ((percentile / 100.0) * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Population standard deviation of the current level in dB
+pub struct StdDev {
+    sums: Vec<f64>,
+    sums_of_squares: Vec<f64>,
+    count: u32,
+}
+
+impl StdDev {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            sums: vec![0.0; num_channels],
+            sums_of_squares: vec![0.0; num_channels],
+            count: 0,
+        }
+    }
+}
+
+impl Statistic for StdDev {
+    fn update(&mut self, state: &AppState) {
+        for (i, &current) in state.current_db.iter().enumerate() {
+            self.sums[i] += current as f64;
+            self.sums_of_squares[i] += (current as f64).powi(2);
+        }
+        self.count += 1;
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        if self.count == 0 {
+            return vec![0.0; self.sums.len()];
+        }
+        let n = self.count as f64;
+        self.sums
+            .iter()
+            .zip(&self.sums_of_squares)
+            .map(|(&sum, &sum_sq)| {
+                let mean = sum / n;
+                (sum_sq / n - mean * mean).max(0.0).sqrt() as f32
+            })
+            .collect()
+    }
+}
+
+/// Equivalent continuous level from the energy accumulated by the audio thread
+pub struct Leq {
+    sum_of_squares: Vec<f64>,
+    sample_count: Vec<u64>,
+}
+
+impl Leq {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            sum_of_squares: vec![0.0; num_channels],
+            sample_count: vec![0; num_channels],
+        }
+    }
+}
+
+impl Statistic for Leq {
+    fn update(&mut self, state: &AppState) {
+        // The audio thread keeps running totals, so the latest snapshot wins
+        self.sum_of_squares.clone_from(&state.sum_of_squares);
+        self.sample_count.clone_from(&state.sample_count);
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        self.sum_of_squares
+            .iter()
+            .zip(&self.sample_count)
+            .map(|(&sum, &count)| {
+                if count > 0 {
+                    amplitude_to_db((sum / count as f64).sqrt())
+                } else {
+                    crate::constants::audio::MIN_DB_LEVEL as f32
+                }
+            })
+            .collect()
+    }
+}
+
+/// Mean of the buffer peaks in the linear domain
+pub struct PeakMean {
+    sums: Vec<f64>,
+    count: u32,
+}
+
+impl PeakMean {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            sums: vec![0.0; num_channels],
+            count: 0,
+        }
+    }
+}
+
+impl Statistic for PeakMean {
+    fn update(&mut self, state: &AppState) {
+        for (sum, &current) in self.sums.iter_mut().zip(&state.current_db) {
+            *sum += crate::smoothing::db_to_amplitude(current) as f64;
+        }
+        self.count += 1;
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        self.sums
+            .iter()
+            .map(|&sum| {
+                if self.count > 0 {
+                    amplitude_to_db(sum / self.count as f64)
+                } else {
+                    crate::constants::audio::MIN_DB_LEVEL as f32
+                }
+            })
+            .collect()
+    }
+}
+
+/// Number of clip events on each channel during the session
+pub struct Clips {
+    start: Option<Vec<u64>>,
+    counts: Vec<u64>,
+}
+
+impl Clips {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            start: None,
+            counts: vec![0; num_channels],
+        }
+    }
+}
+
+impl Statistic for Clips {
+    fn update(&mut self, state: &AppState) {
+        self.start.get_or_insert_with(|| state.clip_count.clone());
+        self.counts.clone_from(&state.clip_count);
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        let Some(start) = &self.start else {
+            return vec![0.0; self.counts.len()];
+        };
+        self.counts
+            .iter()
+            .zip(start)
+            .map(|(&count, &start)| count.saturating_sub(start) as f32)
+            .collect()
+    }
+}
+
+/// Frequency of the dominant tone in Hz, from the zero-crossing rate
+///
+/// Only meaningful for a single steady tone, such as a test signal.
+pub struct Frequency {
+    start: Option<(Vec<u64>, Vec<u64>)>,
+    crossings: Vec<u64>,
+    samples: Vec<u64>,
+    sample_rates: Vec<u32>,
+}
+
+impl Frequency {
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            start: None,
+            crossings: vec![0; num_channels],
+            samples: vec![0; num_channels],
+            sample_rates: vec![0; num_channels],
+        }
+    }
+}
+
+impl Statistic for Frequency {
+    fn update(&mut self, state: &AppState) {
+        if self.start.is_none() {
+            self.start = Some((state.zero_crossings.clone(), state.sample_count.clone()));
+        }
+        self.crossings.clone_from(&state.zero_crossings);
+        self.samples.clone_from(&state.sample_count);
+        self.sample_rates.clone_from(&state.sample_rates);
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        let Some((start_crossings, start_samples)) = &self.start else {
+            return vec![0.0; self.crossings.len()];
+        };
+        (0..self.crossings.len())
+            .map(|i| {
+                let crossings = self.crossings[i].saturating_sub(start_crossings[i]);
+                let samples = self.samples[i].saturating_sub(start_samples[i]);
+                if samples == 0 {
+                    return 0.0;
+                }
+                // Two crossings per cycle
+                (crossings as f64 / 2.0 * self.sample_rates[i] as f64 / samples as f64) as f32
+            })
+            .collect()
+    }
+}
+
+/// Percentile of the RMS level between updates on each channel
+///
+/// Uses the energy of every sample rather than the meter's clamped peaks, so
+/// a quiet or muted input reads below -60 dB.
+pub struct NoiseFloor {
+    last: Option<(Vec<f64>, Vec<u64>)>,
+    levels: Vec<Vec<f32>>,
+    percentile: f32,
+}
+
+impl NoiseFloor {
+    pub fn new(num_channels: usize, percentile: f32) -> Self {
+        Self {
+            last: None,
+            levels: vec![Vec::new(); num_channels],
+            percentile,
+        }
+    }
+}
+
+impl Statistic for NoiseFloor {
+    fn update(&mut self, state: &AppState) {
+        if let Some((last_sums, last_counts)) = &self.last {
+            for (i, levels) in self.levels.iter_mut().enumerate() {
+                let samples = state.sample_count[i].saturating_sub(last_counts[i]);
+                if samples > 0 {
+                    let energy = (state.sum_of_squares[i] - last_sums[i]).max(0.0);
+                    levels.push(amplitude_to_db((energy / samples as f64).sqrt()));
+                }
+            }
+        }
+        self.last = Some((state.sum_of_squares.clone(), state.sample_count.clone()));
+    }
+
+    fn finish(&self) -> Vec<f32> {
+        self.levels
+            .iter()
+            .map(|levels| {
+                if levels.is_empty() {
+                    crate::constants::analyze::FLOOR_DB
+                } else {
+                    percentile(levels, self.percentile)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(statistic: &mut dyn Statistic, levels: &[f32]) {
+        let mut state = AppState::new("test_device".to_string(), 0, 1);
+        for &level in levels {
+            state.current_db[0] = level;
+            statistic.update(&state);
+        }
+    }
+
+    #[test]
+    fn test_min_max_mean() {
+        let levels = [-30.0, -10.0, -20.0];
+        let mut min = StatKind::Min.build(1);
+        let mut max = StatKind::Max.build(1);
+        let mut mean = StatKind::Mean.build(1);
+        feed(min.as_mut(), &levels);
+        feed(max.as_mut(), &levels);
+        feed(mean.as_mut(), &levels);
+
+        assert_eq!(min.finish(), vec![-30.0]);
+        assert_eq!(max.finish(), vec![-10.0]);
+        assert_eq!(mean.finish(), vec![-20.0]);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let levels: Vec<f32> = (1..=100).map(|i| -(i as f32)).collect();
+        assert_eq!(percentile(&levels, 50.0), -51.0);
+        assert_eq!(percentile(&levels, 95.0), -6.0);
+        assert_eq!(percentile(&[], 95.0), -60.0);
+    }
+
+    #[test]
+    fn test_leq_uses_energy() {
+        // A full-scale sine has a mean square of 0.5, i.e. about -3 dB
+        let mut state = AppState::new("test_device".to_string(), 0, 1);
+        state.sum_of_squares[0] = 0.5 * 48000.0;
+        state.sample_count[0] = 48000;
+
+        let mut leq = StatKind::Leq.build(1);
+        leq.update(&state);
+        assert!((leq.finish()[0] + 3.01).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_leq_reports_levels_below_display_floor() {
+        // Mean square of 1e-7 is -70 dB, below the -60 dB meter floor
+        let mut state = AppState::new("test_device".to_string(), 0, 1);
+        state.sum_of_squares[0] = 1e-7 * 48000.0;
+        state.sample_count[0] = 48000;
+
+        let mut leq = StatKind::Leq.build(1);
+        leq.update(&state);
+        assert!((leq.finish()[0] + 70.0).abs() < 0.01);
+
+        // Nothing but digital silence stops at the analysis floor
+        let mut state = AppState::new("test_device".to_string(), 0, 1);
+        state.sample_count[0] = 48000;
+        let mut leq = StatKind::Leq.build(1);
+        leq.update(&state);
+        assert_eq!(leq.finish()[0], crate::constants::analyze::FLOOR_DB);
+    }
+
+    #[test]
+    fn test_peak_mean_averages_amplitude() {
+        let mut peak_mean = StatKind::PeakMean.build(1);
+        feed(peak_mean.as_mut(), &[0.0, -60.0]);
+        // (1.0 + 0.001) / 2 is about -6 dB, unlike the -30 dB mean of the dB values
+        assert!((peak_mean.finish()[0] + 6.01).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_noise_floor_reads_below_meter_floor() {
+        let mut noise = NoiseFloor::new(2, 10.0);
+        let mut state = AppState::new("test_device".to_string(), 0, 2);
+        for _ in 0..5 {
+            // Channel 0 at a mean square of 1e-8 (-80 dB), channel 1 silent
+            state.sum_of_squares[0] += 1e-8 * 480.0;
+            state.sample_count[0] += 480;
+            state.sample_count[1] += 480;
+            noise.update(&state);
+        }
+
+        let levels = noise.finish();
+        assert!((levels[0] + 80.0).abs() < 0.01, "{}", levels[0]);
+        assert_eq!(levels[1], crate::constants::analyze::FLOOR_DB);
+    }
+
+    #[test]
+    fn test_stddev() {
+        let mut stddev = StatKind::Stddev.build(1);
+        feed(stddev.as_mut(), &[-10.0, -20.0, -10.0, -20.0]);
+        assert!((stddev.finish()[0] - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_frequency_from_zero_crossings() {
+        // Two devices at different sample rates
+        let mut frequency = Frequency::new(2);
+        let mut state = AppState::new("test_device".to_string(), 0, 2);
+        state.sample_rates = vec![48000, 44100];
+        state.zero_crossings = vec![10, 0];
+        state.sample_count = vec![4800, 0];
+        frequency.update(&state);
+
+        // One second of a 1 kHz tone later on both
+        state.zero_crossings[0] += 2000;
+        state.sample_count[0] += 48000;
+        state.zero_crossings[1] += 2000;
+        state.sample_count[1] += 44100;
+        frequency.update(&state);
+
+        assert_eq!(frequency.finish(), vec![1000.0, 1000.0]);
+    }
+}
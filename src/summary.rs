@@ -0,0 +1,284 @@
+//! Pass/fail summary of a batch of analyzed files
+
+use crate::analyze::{FileReport, Flags};
+use crate::error::{AppError, AppResult};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// File formats for `--summary`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SummaryFormat {
+    /// Comma-separated values with one file per row
+    Csv,
+    /// JSON with every file's measurements and the aggregate
+    Json,
+}
+
+impl SummaryFormat {
+    /// Guess the format from the file extension, defaulting to CSV
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => SummaryFormat::Json,
+            _ => SummaryFormat::Csv,
+        }
+    }
+}
+
+/// One file's line in the summary
+#[derive(Debug, Serialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<FileReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<Flags>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of checking one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Fail,
+    Error,
+}
+
+impl Status {
+    fn name(self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Fail => "fail",
+            Status::Error => "error",
+        }
+    }
+}
+
+impl Entry {
+    /// Check an analysis result against the QC limits
+    pub fn new(path: &Path, result: AppResult<FileReport>) -> Self {
+        match result {
+            Ok(report) => {
+                let flags = report.flags();
+                Entry {
+                    path: path.to_path_buf(),
+                    status: if flags.passed() {
+                        Status::Pass
+                    } else {
+                        Status::Fail
+                    },
+                    report: Some(report),
+                    flags: Some(flags),
+                    error: None,
+                }
+            }
+            Err(e) => Entry {
+                path: path.to_path_buf(),
+                status: Status::Error,
+                report: None,
+                flags: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Totals across a batch of files
+#[derive(Debug, Default, Serialize)]
+pub struct Aggregate {
+    pub files: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub duration_s: f64,
+    /// Quietest, mean and loudest integrated loudness of files with any
+    pub min_loudness_lufs: Option<f32>,
+    pub mean_loudness_lufs: Option<f32>,
+    pub max_loudness_lufs: Option<f32>,
+    /// Highest true peak of any file
+    pub max_true_peak_db: Option<f32>,
+}
+
+impl Aggregate {
+    /// Sum up `entries`
+    pub fn new(entries: &[Entry]) -> Self {
+        let mut aggregate = Aggregate {
+            files: entries.len(),
+            ..Aggregate::default()
+        };
+        let mut loudness = Vec::new();
+        for entry in entries {
+            match entry.status {
+                Status::Pass => aggregate.passed += 1,
+                Status::Fail => aggregate.failed += 1,
+                Status::Error => aggregate.errors += 1,
+            }
+            let Some(report) = &entry.report else {
+                continue;
+            };
+            aggregate.duration_s += report.duration_s;
+            if report.loudness_lufs.is_finite() {
+                loudness.push(report.loudness_lufs);
+            }
+            let true_peak = report.true_peak_db();
+            aggregate.max_true_peak_db = Some(
+                aggregate
+                    .max_true_peak_db
+                    .map_or(true_peak, |peak| peak.max(true_peak)),
+            );
+        }
+
+        if !loudness.is_empty() {
+            aggregate.min_loudness_lufs = loudness.iter().copied().reduce(f32::min);
+            aggregate.max_loudness_lufs = loudness.iter().copied().reduce(f32::max);
+            aggregate.mean_loudness_lufs =
+                Some(loudness.iter().sum::<f32>() / loudness.len() as f32);
+        }
+        aggregate
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} files: {} passed, {} failed, {} errors, {:.1} s total",
+            self.files, self.passed, self.failed, self.errors, self.duration_s
+        )?;
+        if let (Some(min), Some(mean), Some(max)) = (
+            self.min_loudness_lufs,
+            self.mean_loudness_lufs,
+            self.max_loudness_lufs,
+        ) {
+            write!(
+                f,
+                ", loudness {:.1} / {:.1} / {:.1} LUFS (min / mean / max)",
+                min, mean, max
+            )?;
+        }
+        if let Some(peak) = self.max_true_peak_db {
+            write!(f, ", highest true peak {:.1} dBTP", peak)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write `entries` and their aggregate to `path`
+pub fn write_summary(
+    path: &Path,
+    format: SummaryFormat,
+    entries: &[Entry],
+    aggregate: &Aggregate,
+) -> AppResult<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    match format {
+        SummaryFormat::Csv => {
+            writeln!(
+                out,
+                "path,status,flags,duration_s,sample_rate,channels,loudness_lufs,peak_db,true_peak_db,clips,error"
+            )?;
+            for entry in entries {
+                let flags = entry.flags.map(|f| f.names().join(";")).unwrap_or_default();
+                let measurements = match &entry.report {
+                    Some(report) => format!(
+                        "{:.3},{},{},{},{},{},{}",
+                        report.duration_s,
+                        report.sample_rate,
+                        report.channels.len(),
+                        csv_level(report.loudness_lufs),
+                        csv_level(report.peak_db()),
+                        csv_level(report.true_peak_db()),
+                        report.clips()
+                    ),
+                    None => ",,,,,,".to_string(),
+                };
+                writeln!(
+                    out,
+                    "{},{},{},{},{}",
+                    csv_field(&entry.path.display().to_string()),
+                    entry.status.name(),
+                    flags,
+                    measurements,
+                    csv_field(entry.error.as_deref().unwrap_or_default())
+                )?;
+            }
+        }
+        SummaryFormat::Json => {
+            let summary = serde_json::json!({ "files": entries, "aggregate": aggregate });
+            serde_json::to_writer_pretty(&mut out, &summary).map_err(|e| AppError::Io(e.into()))?;
+            writeln!(out)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Level with one decimal, or an empty field for `-inf` so spreadsheets parse it
+fn csv_level(value: f32) -> String {
+    if value.is_finite() {
+        format!("{:.1}", value)
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze::ChannelReport;
+
+    #[test]
+    fn test_aggregate_and_csv_quoting() {
+        let entries = vec![
+            Entry::new(
+                Path::new("a.wav"),
+                Ok(FileReport {
+                    path: PathBuf::from("a.wav"),
+                    sample_rate: 48000,
+                    duration_s: 2.0,
+                    loudness_lufs: -20.0,
+                    channels: vec![ChannelReport {
+                        channel: 0,
+                        peak_db: -3.0,
+                        true_peak_db: -2.5,
+                        rms_db: -20.0,
+                        loudness_lufs: -20.0,
+                        noise_floor_db: -80.0,
+                        dc_offset: 0.0,
+                        clips: 0,
+                    }],
+                }),
+            ),
+            Entry::new(
+                Path::new("b,c.wav"),
+                Err(AppError::AudioFile("bad".to_string())),
+            ),
+        ];
+        let aggregate = Aggregate::new(&entries);
+        assert_eq!(
+            (aggregate.files, aggregate.passed, aggregate.errors),
+            (2, 1, 1)
+        );
+        assert_eq!(aggregate.mean_loudness_lufs, Some(-20.0));
+
+        assert_eq!(csv_field("b,c.wav"), "\"b,c.wav\"");
+        assert_eq!(csv_level(-20.04), "-20.0");
+        assert_eq!(csv_level(f32::NEG_INFINITY), "");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
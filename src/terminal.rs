@@ -0,0 +1,86 @@
+//! Terminal setup and guaranteed restoration
+
+use crate::error::AppResult;
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{Terminal, backend::CrosstermBackend};
+use std::io::{self, Stdout};
+use std::ops::{Deref, DerefMut};
+use std::sync::Once;
+
+/// Terminal in raw mode on the alternate screen
+///
+/// The terminal is restored when the guard is dropped, including on early
+/// returns, and by a panic hook if the program panics while it is active.
+pub struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    /// Enter raw mode and the alternate screen
+    pub fn new() -> AppResult<Self> {
+        install_panic_hook();
+
+        enable_raw_mode()?;
+        let setup = || -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+            let mut stdout = io::stdout();
+            execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+            Terminal::new(CrosstermBackend::new(stdout))
+        };
+
+        match setup() {
+            Ok(terminal) => Ok(Self { terminal }),
+            Err(e) => {
+                let _ = restore();
+                Err(e.into())
+            }
+        }
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore(); // Nothing useful to do if restoring fails
+    }
+}
+
+/// Leave raw mode and the alternate screen and show the cursor
+pub fn restore() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    )
+}
+
+/// Restore the terminal before the default panic message is printed
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            previous(info);
+        }));
+    });
+}
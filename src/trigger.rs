@@ -0,0 +1,164 @@
+//! Threshold triggers with re-arming, cooldown and command execution
+
+use crate::state::AppState;
+use std::process::Stdio;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Decides when a threshold crossing counts as a new trigger
+///
+/// A channel fires when it crosses the threshold while armed, then stays
+/// disarmed until its level falls back below the threshold. No channel fires
+/// again until the cooldown since the last trigger has passed. A disabled
+/// trigger never fires but keeps tracking levels.
+pub struct Trigger {
+    armed: Vec<bool>,
+    enabled: bool,
+    cooldown: Duration,
+    last_fired: Option<Instant>,
+    count: u64,
+}
+
+impl Trigger {
+    /// Create a trigger with every channel armed
+    pub fn new(num_channels: usize, cooldown: Duration) -> Self {
+        Self {
+            armed: vec![true; num_channels],
+            enabled: true,
+            cooldown,
+            last_fired: None,
+            count: 0,
+        }
+    }
+
+    /// Number of times the trigger has fired
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether threshold crossings can fire the trigger
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Allow or suppress firing
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Feed one snapshot of the levels; returns the channel that fired, if any
+    pub fn update(&mut self, state: &AppState, now: Instant) -> Option<usize> {
+        let cooled_down = self
+            .last_fired
+            .is_none_or(|last| now.duration_since(last) >= self.cooldown);
+        let mut fired = None;
+
+        for (i, armed) in self.armed.iter_mut().enumerate() {
+            if state.threshold_reached[i] {
+                if *armed && self.enabled && cooled_down && fired.is_none() {
+                    *armed = false;
+                    fired = Some(i);
+                }
+            } else if state.current_db[i] < state.threshold_db as f32 {
+                *armed = true;
+            }
+        }
+
+        if fired.is_some() {
+            self.last_fired = Some(now);
+            self.count += 1;
+        }
+        fired
+    }
+}
+
+/// Details of a trigger passed to `--exec` commands as environment variables
+pub struct TriggerInfo {
+    pub channel: usize,
+    pub label: Option<String>,
+    pub level_db: f32,
+    pub threshold_db: i32,
+    pub count: u64,
+}
+
+/// Start `command` through the platform shell for a trigger
+///
+/// The command's output is discarded so it cannot draw over the UI.
+pub fn spawn_command(command: &str, info: &TriggerInfo) -> std::io::Result<tokio::process::Child> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    if let Some(label) = &info.label {
+        shell.env("SOUNDCHECK_LABEL", label);
+    }
+    shell
+        .arg(command)
+        .env("SOUNDCHECK_CHANNEL", info.channel.to_string())
+        .env("SOUNDCHECK_LEVEL_DB", format!("{:.1}", info.level_db))
+        .env("SOUNDCHECK_THRESHOLD_DB", info.threshold_db.to_string())
+        .env("SOUNDCHECK_TRIGGER_COUNT", info.count.to_string())
+        .env("SOUNDCHECK_TIMESTAMP", format!("{:.3}", timestamp))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+/// Wait for a trigger command to finish and log how it ended
+pub async fn wait_for_command(mut child: tokio::process::Child) {
+    match child.wait().await {
+        Ok(status) if status.success() => tracing::debug!("Trigger command finished"),
+        Ok(status) => tracing::warn!(%status, "Trigger command failed"),
+        Err(e) => tracing::error!(error = %e, "Could not wait for trigger command"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(level_db: f32, crossed: bool) -> AppState {
+        let mut state = AppState::new("test_device".to_string(), -20, 1);
+        state.current_db[0] = level_db;
+        state.threshold_reached[0] = crossed;
+        state
+    }
+
+    #[test]
+    fn test_rearms_after_falling_below_threshold() {
+        let mut trigger = Trigger::new(1, Duration::ZERO);
+        let now = Instant::now();
+
+        assert_eq!(trigger.update(&state(-10.0, true), now), Some(0));
+        // Still above the threshold: not re-armed yet
+        assert_eq!(trigger.update(&state(-10.0, true), now), None);
+        assert_eq!(trigger.update(&state(-40.0, false), now), None);
+        assert_eq!(trigger.update(&state(-10.0, true), now), Some(0));
+        assert_eq!(trigger.count(), 2);
+    }
+
+    #[test]
+    fn test_honors_cooldown() {
+        let mut trigger = Trigger::new(1, Duration::from_secs(1));
+        let start = Instant::now();
+
+        assert_eq!(trigger.update(&state(-10.0, true), start), Some(0));
+        trigger.update(&state(-40.0, false), start);
+        let during = start + Duration::from_millis(500);
+        assert_eq!(trigger.update(&state(-10.0, true), during), None);
+        trigger.update(&state(-40.0, false), during);
+        let after = start + Duration::from_millis(1500);
+        assert_eq!(trigger.update(&state(-10.0, true), after), Some(0));
+    }
+}
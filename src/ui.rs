@@ -2,14 +2,15 @@
 
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
+use serde::{Deserialize, Serialize};
 
 /// Application state for UI rendering
-#[derive(Clone)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct UiState {
     pub device_name: String,
     pub current_db: Vec<f32>,
@@ -17,6 +18,18 @@ pub struct UiState {
     pub threshold_db: i32,
     pub min_db: i32,
     pub status: String,
+    /// Hardware index of each channel
+    #[serde(default)]
+    pub channels: Vec<usize>,
+    /// Label of each channel from `--labels`; empty when none were given
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Devices feeding the meters, when there is more than one
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// Index into `devices` for each channel
+    #[serde(default)]
+    pub channel_devices: Vec<usize>,
 }
 
 /// Create a gradient bar showing audio levels
@@ -157,40 +170,169 @@ pub fn render_ui(f: &mut Frame, state: &UiState) {
             (chunks[3].width as usize).saturating_sub(crate::constants::ui::BAR_BORDER_WIDTH);
         let bar_line = create_gradient_bar(bar_width, db_ratio);
         let label_line = create_db_labels(bar_width, state.threshold_db, state.min_db);
+        // A lone default channel needs no name
+        let title = if state.labels.is_empty() && state.channels.iter().all(|&ch| ch == 0) {
+            format!(
+                "Current dB: {:.1} (Raw: {:.1})",
+                state.display_db[0], state.current_db[0]
+            )
+        } else {
+            format!(
+                "Channel {}: {:.1} dB (Raw: {:.1})",
+                channel_name(state, 0),
+                state.display_db[0],
+                state.current_db[0]
+            )
+        };
+        let gauge = Paragraph::new(vec![bar_line, label_line])
+            .block(Block::default().title(title).borders(Borders::ALL));
+        f.render_widget(gauge, chunks[3]);
+    } else if state.devices.len() > 1 {
+        // One bordered group per device, holding that device's channels
+        let constraints: Vec<Constraint> = (0..state.devices.len())
+            .map(|_| Constraint::Ratio(1, state.devices.len() as u32))
+            .collect();
+        let device_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(chunks[3]);
+
+        for (d, (name, chunk)) in state.devices.iter().zip(device_chunks.iter()).enumerate() {
+            let block = Block::default().title(name.as_str()).borders(Borders::ALL);
+            let inner = block.inner(*chunk);
+            f.render_widget(block, *chunk);
+            let channels: Vec<usize> = (0..num_channels)
+                .filter(|&i| state.channel_devices.get(i) == Some(&d))
+                .collect();
+            render_channel_gauges(f, state, &channels, inner);
+        }
+    } else {
+        let channels: Vec<usize> = (0..num_channels).collect();
+        render_channel_gauges(f, state, &channels, chunks[3]);
+    }
+}
+
+/// Hardware index and label of the meter at position `i`
+fn channel_name(state: &UiState, i: usize) -> String {
+    crate::channels::describe(
+        state.channels.get(i).copied().unwrap_or(i),
+        state.labels.get(i).map(String::as_str),
+    )
+}
+
+/// Stack one gauge per channel in `channels` inside `area`
+fn render_channel_gauges(f: &mut Frame, state: &UiState, channels: &[usize], area: Rect) {
+    let min_db = state.min_db as f32;
+    let db_range = -min_db;
+    let constraints: Vec<Constraint> = channels
+        .iter()
+        .map(|_| Constraint::Ratio(1, channels.len() as u32))
+        .collect();
+    let channel_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+
+    for (&i, chunk) in channels.iter().zip(channel_chunks.iter()) {
+        let db_ratio = ((state.display_db[i] - min_db) / db_range).clamp(0.0, 1.0) as f64;
+        let bar_width =
+            (chunk.width as usize).saturating_sub(crate::constants::ui::BAR_BORDER_WIDTH);
+        let bar_line = create_gradient_bar(bar_width, db_ratio);
+        let label_line = create_db_labels(bar_width, state.threshold_db, state.min_db);
         let gauge = Paragraph::new(vec![bar_line, label_line]).block(
             Block::default()
                 .title(format!(
-                    "Current dB: {:.1} (Raw: {:.1})",
-                    state.display_db[0], state.current_db[0]
+                    "Channel {}: {:.1} dB (Raw: {:.1})",
+                    channel_name(state, i),
+                    state.display_db[i],
+                    state.current_db[i]
                 ))
                 .borders(Borders::ALL),
         );
-        f.render_widget(gauge, chunks[3]);
-    } else {
-        // For multiple channels, split the area into sub-chunks
-        let constraints: Vec<Constraint> = (0..num_channels)
-            .map(|_| Constraint::Ratio(1, num_channels as u32))
-            .collect();
-        let channel_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(constraints)
-            .split(chunks[3]);
+        f.render_widget(gauge, *chunk);
+    }
+}
+
+/// One input device in the scanner
+#[derive(Clone, Default)]
+pub struct ScanDevice {
+    pub name: String,
+    /// Stream format, or why the device could not be opened
+    pub detail: String,
+    pub channels: Vec<ScanChannel>,
+}
+
+/// Level of one channel in the scanner
+#[derive(Clone, Copy, Default)]
+pub struct ScanChannel {
+    pub display_db: f32,
+    /// Signal was above the threshold recently
+    pub active: bool,
+}
+
+/// Everything the scanner shows
+#[derive(Clone, Default)]
+pub struct ScanState {
+    pub devices: Vec<ScanDevice>,
+    pub min_db: i32,
+}
+
+/// Render a compact meter for every channel of every device
+///
+/// Devices and their channels flow down the screen and continue in further
+/// columns when they do not fit.
+pub fn render_scan(f: &mut Frame, state: &ScanState) {
+    let active = state
+        .devices
+        .iter()
+        .flat_map(|device| &device.channels)
+        .filter(|channel| channel.active)
+        .count();
+    let block = Block::default()
+        .title(format!(
+            "Scanning {} devices: {} channels with signal. Press q or Escape to quit.",
+            state.devices.len(),
+            active
+        ))
+        .borders(Borders::ALL);
+    let area = block.inner(f.size());
+    f.render_widget(block, f.size());
 
-        for (i, chunk) in channel_chunks.iter().enumerate() {
-            let db_ratio = ((state.display_db[i] - min_db) / db_range).clamp(0.0, 1.0) as f64;
-            let bar_width =
-                (chunk.width as usize).saturating_sub(crate::constants::ui::BAR_BORDER_WIDTH);
-            let bar_line = create_gradient_bar(bar_width, db_ratio);
-            let label_line = create_db_labels(bar_width, state.threshold_db, state.min_db);
-            let gauge = Paragraph::new(vec![bar_line, label_line]).block(
-                Block::default()
-                    .title(format!(
-                        "Channel {}: {:.1} dB (Raw: {:.1})",
-                        i, state.display_db[i], state.current_db[i]
-                    ))
-                    .borders(Borders::ALL),
-            );
-            f.render_widget(gauge, *chunk);
+    let min_db = state.min_db as f32;
+    let mut lines = Vec::new();
+    for device in &state.devices {
+        lines.push(Line::from(vec![
+            Span::styled(device.name.clone(), Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!(" ({})", device.detail),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+        for (ch, channel) in device.channels.iter().enumerate() {
+            let ratio = ((channel.display_db - min_db) / -min_db).clamp(0.0, 1.0) as f64;
+            let label_style = if channel.active {
+                Style::default().fg(Color::Black).bg(Color::Green)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let mut spans = vec![Span::styled(format!(" {:>3} ", ch), label_style)];
+            spans.push(Span::raw(" "));
+            spans.extend(create_gradient_bar(crate::constants::scan::BAR_WIDTH, ratio).spans);
+            spans.push(Span::raw(format!(
+                " {:>6.1}",
+                channel.display_db.max(min_db)
+            )));
+            lines.push(Line::from(spans));
         }
     }
+
+    let height = (area.height as usize).max(1);
+    let columns = lines.len().div_ceil(height).max(1);
+    let column_areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+        .split(area);
+    for (chunk, column_area) in lines.chunks(height).zip(column_areas.iter()) {
+        f.render_widget(Paragraph::new(chunk.to_vec()), *column_area);
+    }
 }
@@ -0,0 +1,187 @@
+//! Browser meter served over HTTP with Server-Sent Events
+//!
+//! `/` serves a self-contained page and `/events` streams the same
+//! `ui::UiState` the terminal UI renders, as JSON.
+
+use crate::http;
+use crate::ui::UiState;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+/// Serve the meter page and level stream until the task is dropped
+pub async fn serve(listener: TcpListener, ui_rx: watch::Receiver<UiState>) {
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                crate::http::accept_failed("web", e).await;
+                continue;
+            }
+        };
+        let ui_rx = ui_rx.clone();
+        tokio::spawn(async move {
+            let result = match http::read_request(&mut stream).await {
+                Some((method, path)) if method == "GET" && path == "/" => {
+                    http::write_response(
+                        &mut stream,
+                        "200 OK",
+                        "text/html; charset=utf-8",
+                        PAGE.as_bytes(),
+                    )
+                    .await
+                }
+                Some((method, path)) if method == "GET" && path == "/events" => {
+                    tracing::info!(%peer, "Web meter connected");
+                    stream_levels(&mut stream, ui_rx).await
+                }
+                Some(_) => http::not_found(&mut stream).await,
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                tracing::debug!(%peer, error = %e, "Web meter connection closed");
+            }
+        });
+    }
+}
+
+/// Send UI snapshots as Server-Sent Events until the client goes away
+async fn stream_levels(
+    stream: &mut TcpStream,
+    mut ui_rx: watch::Receiver<UiState>,
+) -> std::io::Result<()> {
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+        )
+        .await?;
+
+    let mut interval = tokio::time::interval(Duration::from_millis(
+        crate::constants::net::STREAM_INTERVAL_MS,
+    ));
+    loop {
+        interval.tick().await;
+        if ui_rx.has_changed().is_err() {
+            // Monitoring stopped
+            return Ok(());
+        }
+        let json =
+            serde_json::to_string(&*ui_rx.borrow_and_update()).map_err(std::io::Error::other)?;
+        stream
+            .write_all(format!("data: {}\n\n", json).as_bytes())
+            .await?;
+    }
+}
+
+/// Self-contained meter page
+const PAGE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>soundcheck</title>
+<style>
+  body { background: #111; color: #ddd; font-family: ui-monospace, monospace; margin: 1rem; }
+  h1 { font-size: 1.1rem; margin: 0 0 .25rem; }
+  #status { color: #999; margin-bottom: 1rem; }
+  .channel { margin-bottom: 1rem; }
+  .label { display: flex; justify-content: space-between; margin-bottom: .25rem; }
+  .meter { position: relative; height: 2.5rem; border: 1px solid #555;
+           background: linear-gradient(to right, #0a0 0 33.3%, #cc0 33.3% 66.6%, #c00 66.6%); }
+  .cover { position: absolute; top: 0; right: 0; bottom: 0; background: #222; }
+  .threshold { position: absolute; top: -4px; bottom: -4px; width: 2px; background: #fff; }
+  .scale { display: flex; justify-content: space-between; color: #888; font-size: .8rem; }
+</style>
+</head>
+<body>
+<h1 id="device">Connecting...</h1>
+<div id="status"></div>
+<div id="channels"></div>
+<script>
+const channels = document.getElementById("channels");
+
+function ratio(db, min) {
+  return Math.min(Math.max((db - min) / -min, 0), 1);
+}
+
+function render(state) {
+  document.getElementById("device").textContent = state.device_name;
+  document.getElementById("status").textContent = state.status;
+  while (channels.children.length < state.display_db.length) {
+    const el = document.createElement("div");
+    el.className = "channel";
+    el.innerHTML = '<div class="label"><span class="name"></span><span class="value"></span></div>' +
+      '<div class="meter"><div class="cover"></div><div class="threshold"></div></div>' +
+      '<div class="scale"><span class="min"></span><span>0</span></div>';
+    channels.appendChild(el);
+  }
+  state.display_db.forEach((db, i) => {
+    const el = channels.children[i];
+    const channel = state.channels && state.channels.length > i ? state.channels[i] : i;
+    const label = state.labels && state.labels[i];
+    let name = state.display_db.length > 1 || label || channel !== 0 ? "Channel " + channel : "Level";
+    if (label) {
+      name += " (" + label + ")";
+    }
+    if (state.devices && state.devices.length > 1) {
+      name = state.devices[state.channel_devices[i]] + " / " + name;
+    }
+    el.querySelector(".name").textContent = name;
+    el.querySelector(".value").textContent =
+      db.toFixed(1) + " dB (Raw: " + state.current_db[i].toFixed(1) + ")";
+    el.querySelector(".cover").style.width = (100 - ratio(db, state.min_db) * 100) + "%";
+    el.querySelector(".threshold").style.left = (ratio(state.threshold_db, state.min_db) * 100) + "%";
+    el.querySelector(".min").textContent = state.min_db;
+  });
+}
+
+const events = new EventSource("/events");
+events.onmessage = (e) => render(JSON.parse(e.data));
+events.onerror = () => { document.getElementById("status").textContent = "Disconnected, retrying..."; };
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    async fn get(addr: std::net::SocketAddr, path: &str, len: usize) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: test\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut buf = vec![0; len];
+        let mut read = 0;
+        while read < len {
+            match stream.read(&mut buf[read..]).await.unwrap() {
+                0 => break,
+                n => read += n,
+            }
+        }
+        String::from_utf8_lossy(&buf[..read]).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_serves_page_and_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (ui_tx, ui_rx) = watch::channel(UiState {
+            device_name: "test_device".to_string(),
+            ..UiState::default()
+        });
+        tokio::spawn(serve(listener, ui_rx));
+
+        let page = get(addr, "/", 64).await;
+        assert!(page.starts_with("HTTP/1.1 200 OK"));
+
+        let events = get(addr, "/events", 256).await;
+        assert!(events.contains("text/event-stream"));
+        assert!(events.contains("\"device_name\":\"test_device\""));
+        drop(ui_tx);
+    }
+}
@@ -0,0 +1,31 @@
+//! Runs the binary the way scripts and CI do, without a terminal
+
+use std::process::{Command, Output, Stdio};
+
+fn soundcheck(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_soundcheck"))
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run soundcheck")
+}
+
+#[test]
+fn test_max_runs_without_a_terminal() {
+    let output = soundcheck(&[
+        "max",
+        "--seconds",
+        "0.5",
+        "--quiet",
+        "--simulate",
+        "sine:1000:-12",
+    ]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "-12");
+}
+
+#[test]
+fn test_detect_exit_code_without_a_terminal() {
+    let output = soundcheck(&["detect", "--simulate", "burst:0.2:-6", "--threshold=-20"]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+}